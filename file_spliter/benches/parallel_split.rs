@@ -0,0 +1,105 @@
+//! Compares the line-by-line split with the parallel memory-mapped one.
+//!
+//! ```bash
+//! cargo bench --features parallel
+//! SPLIT_BENCH_LINES=20000000 SPLIT_BENCH_PARTS=32 cargo bench --features parallel
+//! ```
+
+use file_spliter::{SplitConfig, SplitOptions, split_file_with_options};
+use std::env;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+const RUNS: usize = 3;
+
+fn env_or(name: &str, default: usize) -> usize {
+    env::var(name)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+fn write_input(path: &Path, lines: usize) {
+    let mut writer = BufWriter::new(File::create(path).expect("create bench input"));
+    for i in 1..=lines {
+        writeln!(
+            writer,
+            "{:>10} | 2024-05-01T12:00:00Z INFO request served in {} ms",
+            i,
+            i % 997
+        )
+        .expect("write bench input");
+    }
+    writer.flush().expect("flush bench input");
+}
+
+/// Non-overlapping ranges covering the whole input.
+fn configs(dir: &Path, prefix: &str, lines: usize, parts: usize) -> Vec<SplitConfig> {
+    let size = lines.div_ceil(parts);
+    (0..parts)
+        .map(|i| {
+            let start = i * size + 1;
+            let end = ((i + 1) * size).min(lines);
+            let path = dir.join(format!("{}-{:03}.txt", prefix, i + 1));
+            SplitConfig::new(start, end, path.to_string_lossy().into_owned()).expect("valid range")
+        })
+        .collect()
+}
+
+fn time_split(input: &Path, parts: &[SplitConfig], parallel: bool) -> Duration {
+    let mut best = Duration::MAX;
+    for _ in 0..RUNS {
+        let options = SplitOptions {
+            parallel,
+            ..SplitOptions::default()
+        };
+        let started = Instant::now();
+        split_file_with_options(input, parts, options).expect("split failed");
+        best = best.min(started.elapsed());
+    }
+    best
+}
+
+fn main() {
+    let lines = env_or("SPLIT_BENCH_LINES", 5_000_000);
+    let parts = env_or("SPLIT_BENCH_PARTS", 16);
+
+    let dir = env::temp_dir().join(format!("file_spliter_bench_{}", std::process::id()));
+    fs::create_dir_all(&dir).expect("create bench dir");
+    let input = dir.join("input.txt");
+    write_input(&input, lines);
+    let size_mb = fs::metadata(&input).map(|m| m.len()).unwrap_or(0) as f64 / 1e6;
+
+    let sequential_parts = configs(&dir, "seq", lines, parts);
+    let parallel_parts = configs(&dir, "par", lines, parts);
+
+    let sequential = time_split(&input, &sequential_parts, false);
+    let parallel = time_split(&input, &parallel_parts, true);
+
+    // Both paths must produce the same bytes
+    for (a, b) in sequential_parts.iter().zip(&parallel_parts) {
+        let same = fs::read(&a.output_path).ok() == fs::read(&b.output_path).ok();
+        assert!(same, "{} and {} differ", a.output_path, b.output_path);
+    }
+
+    println!(
+        "input: {} lines, {:.1} MB, {} parts (best of {})",
+        lines, size_mb, parts, RUNS
+    );
+    for (name, took) in [("sequential", sequential), ("parallel", parallel)] {
+        println!(
+            "{:<10} {:>8.1} ms  {:>8.1} MB/s",
+            name,
+            took.as_secs_f64() * 1e3,
+            size_mb / took.as_secs_f64()
+        );
+    }
+    println!(
+        "speedup    {:>8.2}x",
+        sequential.as_secs_f64() / parallel.as_secs_f64()
+    );
+
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,19 @@
+use crate::{SplitConfig, SplitOptions, split_file_with_options};
+use std::path::PathBuf;
+
+/// Async version of [`split_file_with_options`] for use inside a tokio
+/// runtime.
+///
+/// The split is pure blocking file I/O, so it runs on tokio's blocking
+/// thread pool instead of stalling an async worker thread. Everything it
+/// needs is moved into the task, hence the owned arguments.
+pub async fn split_file_async(
+    input_path: impl Into<PathBuf>,
+    parts: Vec<SplitConfig>,
+    options: SplitOptions,
+) -> Result<String, String> {
+    let input_path = input_path.into();
+    tokio::task::spawn_blocking(move || split_file_with_options(&input_path, &parts, options))
+        .await
+        .map_err(|e| format!("Split task failed: {}", e))?
+}
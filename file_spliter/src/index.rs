@@ -0,0 +1,385 @@
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+const MAGIC: &[u8; 8] = b"SPLIDX01";
+
+/// Lines between two stored offsets. Finding a line costs at most this many
+/// skipped lines after the seek, while the index stays around 8 bytes per
+/// thousand lines.
+const DEFAULT_STRIDE: u32 = 1024;
+
+/// Sparse map from line numbers to byte offsets, saved next to the input as
+/// `<file>.lidx` so range extraction can seek instead of reading every
+/// preceding line.
+///
+/// The index remembers the size and modification time of the file it was
+/// built from and is ignored once either changes.
+#[derive(Debug, Clone)]
+pub struct LineIndex {
+    file_size: u64,
+    modified: (u64, u32),
+    stride: u32,
+    total_lines: usize,
+    /// `offsets[k]` is where line `k * stride + 1` starts.
+    offsets: Vec<u64>,
+}
+
+impl LineIndex {
+    /// Scans `input_path` once and records where every `stride`-th line
+    /// starts. Lines are counted the same way `BufRead::lines` does.
+    pub fn build<P: AsRef<Path>>(input_path: P) -> Result<Self, String> {
+        let path = input_path.as_ref();
+        let (file_size, modified) = file_stamp(path)?;
+        let input_file = File::open(path).map_err(|e| format!("Open error: {}", e))?;
+        let mut reader = BufReader::with_capacity(1 << 20, input_file);
+
+        let stride = DEFAULT_STRIDE;
+        let mut offsets = vec![0];
+        let mut newlines: usize = 0;
+        let mut position: u64 = 0;
+        let mut last_byte = None;
+
+        loop {
+            let buffer = reader
+                .fill_buf()
+                .map_err(|e| format!("Read error at byte {}: {}", position, e))?;
+            if buffer.is_empty() {
+                break;
+            }
+
+            for (i, _) in buffer.iter().enumerate().filter(|(_, b)| **b == b'\n') {
+                newlines += 1;
+                if newlines.is_multiple_of(stride as usize) {
+                    offsets.push(position + i as u64 + 1);
+                }
+            }
+
+            last_byte = buffer.last().copied();
+            let len = buffer.len();
+            position += len as u64;
+            reader.consume(len);
+        }
+
+        // A last line without a trailing newline still counts
+        let total_lines = match last_byte {
+            Some(b) if b != b'\n' => newlines + 1,
+            _ => newlines,
+        };
+        // Drop a checkpoint that points at the very end of the file
+        if offsets.len() > 1 && *offsets.last().unwrap_or(&0) >= file_size {
+            offsets.pop();
+        }
+
+        Ok(LineIndex {
+            file_size,
+            modified,
+            stride,
+            total_lines,
+            offsets,
+        })
+    }
+
+    /// Where the index of `input_path` lives: the same path with `.lidx`
+    /// appended.
+    pub fn sidecar_path<P: AsRef<Path>>(input_path: P) -> PathBuf {
+        let mut name = input_path.as_ref().as_os_str().to_owned();
+        name.push(".lidx");
+        PathBuf::from(name)
+    }
+
+    /// Loads the sidecar of `input_path`. Returns `None` if there is none or
+    /// the file changed since the index was built.
+    pub fn load<P: AsRef<Path>>(input_path: P) -> Result<Option<Self>, String> {
+        let path = input_path.as_ref();
+        let sidecar = Self::sidecar_path(path);
+        let Ok(file) = File::open(&sidecar) else {
+            return Ok(None);
+        };
+
+        let index = read_index(BufReader::new(file))
+            .map_err(|e| format!("Corrupt line index '{}': {}", sidecar.display(), e))?;
+        let (file_size, modified) = file_stamp(path)?;
+        if index.file_size != file_size || index.modified != modified {
+            return Ok(None);
+        }
+        Ok(Some(index))
+    }
+
+    /// Loads a valid sidecar, or builds and saves a fresh one.
+    pub fn open_or_build<P: AsRef<Path>>(input_path: P) -> Result<Self, String> {
+        let path = input_path.as_ref();
+        if let Some(index) = Self::load(path)? {
+            return Ok(index);
+        }
+        let index = Self::build(path)?;
+        index.save(path)?;
+        Ok(index)
+    }
+
+    /// Writes the index next to `input_path` and returns the sidecar path.
+    pub fn save<P: AsRef<Path>>(&self, input_path: P) -> Result<PathBuf, String> {
+        let sidecar = Self::sidecar_path(input_path);
+        let file = File::create(&sidecar)
+            .map_err(|e| format!("Cannot create index '{}': {}", sidecar.display(), e))?;
+
+        let mut writer = BufWriter::new(file);
+        let result = (|| {
+            writer.write_all(MAGIC)?;
+            writer.write_all(&self.file_size.to_le_bytes())?;
+            writer.write_all(&self.modified.0.to_le_bytes())?;
+            writer.write_all(&self.modified.1.to_le_bytes())?;
+            writer.write_all(&self.stride.to_le_bytes())?;
+            writer.write_all(&(self.total_lines as u64).to_le_bytes())?;
+            writer.write_all(&(self.offsets.len() as u64).to_le_bytes())?;
+            for offset in &self.offsets {
+                writer.write_all(&offset.to_le_bytes())?;
+            }
+            writer.flush()
+        })();
+        result.map_err(|e| format!("Cannot write index '{}': {}", sidecar.display(), e))?;
+
+        Ok(sidecar)
+    }
+
+    pub fn total_lines(&self) -> usize {
+        self.total_lines
+    }
+
+    /// Nearest stored position at or before `line` (1-based): the line
+    /// number found there and its byte offset.
+    pub fn seek_point(&self, line: usize) -> (usize, u64) {
+        let slot = (line.saturating_sub(1) / self.stride as usize).min(self.offsets.len() - 1);
+        (slot * self.stride as usize + 1, self.offsets[slot])
+    }
+}
+
+/// Size and modification time used to tell whether an index is stale.
+fn file_stamp(path: &Path) -> Result<(u64, (u64, u32)), String> {
+    let metadata =
+        fs::metadata(path).map_err(|e| format!("Cannot stat '{}': {}", path.display(), e))?;
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| (d.as_secs(), d.subsec_nanos()))
+        .unwrap_or((0, 0));
+    Ok((metadata.len(), modified))
+}
+
+fn read_index<R: Read>(mut reader: R) -> std::io::Result<LineIndex> {
+    fn u64_from<R: Read>(reader: &mut R) -> std::io::Result<u64> {
+        let mut bytes = [0; 8];
+        reader.read_exact(&mut bytes)?;
+        Ok(u64::from_le_bytes(bytes))
+    }
+    fn u32_from<R: Read>(reader: &mut R) -> std::io::Result<u32> {
+        let mut bytes = [0; 4];
+        reader.read_exact(&mut bytes)?;
+        Ok(u32::from_le_bytes(bytes))
+    }
+    let invalid = |msg: &str| std::io::Error::new(std::io::ErrorKind::InvalidData, msg.to_string());
+
+    let mut magic = [0; 8];
+    reader.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(invalid("not a line index"));
+    }
+
+    let file_size = u64_from(&mut reader)?;
+    let modified = (u64_from(&mut reader)?, u32_from(&mut reader)?);
+    let stride = u32_from(&mut reader)?;
+    let total_lines = u64_from(&mut reader)? as usize;
+    let count = u64_from(&mut reader)? as usize;
+    if stride == 0 || count == 0 {
+        return Err(invalid("empty index"));
+    }
+
+    let mut offsets = Vec::with_capacity(count.min(1 << 24));
+    for _ in 0..count {
+        offsets.push(u64_from(&mut reader)?);
+    }
+
+    Ok(LineIndex {
+        file_size,
+        modified,
+        stride,
+        total_lines,
+        offsets,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        CollapseBlankLines, SplitConfig, SplitOptions, SplitUnit, split_file,
+        split_file_with_options,
+    };
+    use std::sync::{Arc, Mutex};
+
+    /// A fresh directory for one test, removed by the caller.
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "file_spliter_index_{}_{}",
+            std::process::id(),
+            name
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Lines of varying length, the last one without a newline.
+    fn sample(lines: usize) -> String {
+        let mut text: String = (1..lines)
+            .map(|i| format!("{}{}\n", i, "x".repeat(i % 7)))
+            .collect();
+        text.push_str("last");
+        text
+    }
+
+    fn split_range(dir: &Path, input: &Path, start: usize, end: usize) -> String {
+        let output = dir.join(format!("part_{}_{}.txt", start, end));
+        let config = SplitConfig::new(start, end, output.display().to_string()).unwrap();
+        split_file(input, &[config]).unwrap();
+        fs::read_to_string(output).unwrap()
+    }
+
+    #[test]
+    fn seek_points_are_line_starts() {
+        let dir = scratch_dir("seek");
+        let input = dir.join("input.txt");
+        let text = sample(3000);
+        fs::write(&input, &text).unwrap();
+
+        let index = LineIndex::build(&input).unwrap();
+        let starts: Vec<usize> = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        assert_eq!(index.total_lines(), 3000);
+        for line in [1, 2, 1024, 1025, 1026, 2048, 2049, 3000, 9999] {
+            let (found, offset) = index.seek_point(line);
+            assert!(found <= line.min(3000), "line {line}");
+            assert_eq!(offset as usize, starts[found - 1], "line {line}");
+        }
+        assert_eq!(index.seek_point(3000).0, 2049);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn seeking_matches_a_full_scan() {
+        let dir = scratch_dir("split");
+        let input = dir.join("input.txt");
+        fs::write(&input, sample(3000)).unwrap();
+
+        let ranges = [
+            (1, 10),
+            (1020, 1030),
+            (2047, 2050),
+            (2990, 3000),
+            (2500, 4000),
+        ];
+        let scanned: Vec<String> = ranges
+            .iter()
+            .map(|&(start, end)| split_range(&dir, &input, start, end))
+            .collect();
+
+        LineIndex::build(&input).unwrap().save(&input).unwrap();
+        assert!(LineIndex::load(&input).unwrap().is_some());
+        for (&(start, end), expected) in ranges.iter().zip(&scanned) {
+            assert_eq!(&split_range(&dir, &input, start, end), expected);
+        }
+        assert!(scanned[3].ends_with("last\n"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn stale_or_corrupt_sidecar() {
+        let dir = scratch_dir("stale");
+        let input = dir.join("input.txt");
+        fs::write(&input, sample(2000)).unwrap();
+        let sidecar = LineIndex::build(&input).unwrap().save(&input).unwrap();
+        assert_eq!(sidecar, LineIndex::sidecar_path(&input));
+
+        // Lines added in front: the old offsets would point at the wrong lines
+        let changed = format!("new\n{}", sample(2000));
+        fs::write(&input, &changed).unwrap();
+        assert!(LineIndex::load(&input).unwrap().is_none());
+        assert_eq!(split_range(&dir, &input, 1500, 1500), "1499x\n");
+
+        // open_or_build replaces it with a fresh one
+        let rebuilt = LineIndex::open_or_build(&input).unwrap();
+        assert_eq!(rebuilt.total_lines(), 2001);
+        assert!(LineIndex::load(&input).unwrap().is_some());
+
+        fs::write(&sidecar, b"SPLIDX01 truncated").unwrap();
+        assert!(
+            LineIndex::load(&input)
+                .unwrap_err()
+                .starts_with("Corrupt line index")
+        );
+        fs::write(&sidecar, b"not an index at all").unwrap();
+        assert!(LineIndex::load(&input).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// Splits with `options`, then again with a saved index, and checks
+    /// both give the same part.
+    fn same_with_index(name: &str, text: &str, config: SplitConfig, options: SplitOptions) {
+        let dir = scratch_dir(name);
+        let input = dir.join("input.txt");
+        fs::write(&input, text).unwrap();
+        let output = dir.join("part.txt");
+        let config = SplitConfig {
+            output_path: output.display().to_string(),
+            ..config
+        };
+
+        let split = || {
+            split_file_with_options(&input, std::slice::from_ref(&config), options.clone())
+                .unwrap();
+            fs::read_to_string(&output).unwrap()
+        };
+        let scanned = split();
+        LineIndex::build(&input).unwrap().save(&input).unwrap();
+        assert_eq!(split(), scanned);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn records_are_counted_from_the_top() {
+        // Two-line records after a header: line 1025, where a seek would
+        // land, closes a quoted field
+        let records: String = (1..=1000)
+            .map(|i| format!("{},\"a\n{}b\"\n", i, i))
+            .collect();
+        let text = format!("id,note\n{}", records);
+        let config = SplitConfig::new(1100, 1199, String::new())
+            .unwrap()
+            .with_interleave(2, 0)
+            .unwrap();
+        let options = SplitOptions {
+            record_unit: SplitUnit::CsvRecords,
+            ..SplitOptions::default()
+        };
+        same_with_index("records", &text, config, options);
+    }
+
+    #[test]
+    fn transforms_see_every_line() {
+        let text: String = (1..=2000)
+            .map(|i| match i {
+                1020..=1030 => "\n".to_string(),
+                _ => format!("{}\n", i),
+            })
+            .collect();
+        let options = SplitOptions {
+            transforms: vec![Arc::new(Mutex::new(CollapseBlankLines::default()))],
+            ..SplitOptions::default()
+        };
+        let config = SplitConfig::new(1025, 1035, String::new()).unwrap();
+        same_with_index("transforms", &text, config, options);
+    }
+}
@@ -0,0 +1,508 @@
+use serde::de::IgnoredAny;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+/// How much goes into each JSON part.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsonChunkLimit {
+    /// At most this many records per part.
+    Records(usize),
+    /// At most this many bytes of records per part. A single record larger
+    /// than the budget still gets a part of its own.
+    Bytes(u64),
+}
+
+impl JsonChunkLimit {
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            JsonChunkLimit::Records(0) | JsonChunkLimit::Bytes(0) => {
+                Err("JSON chunk limit must be at least 1.".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether a record of `size` bytes still fits next to `count` records
+    /// that already take `used` bytes.
+    fn fits(&self, count: usize, used: u64, size: u64) -> bool {
+        match *self {
+            JsonChunkLimit::Records(max) => count < max,
+            JsonChunkLimit::Bytes(max) => count == 0 || used + size <= max,
+        }
+    }
+}
+
+// =========================================================================
+// HELPER 1: PART WRITER
+// Opens parts lazily (the number of parts is only known at the end) and
+// removes everything it created if the split fails half-way.
+// =========================================================================
+pub(crate) struct PartWriter<F: FnMut(usize) -> String> {
+    part_path: F,
+    pub(crate) created: Vec<String>,
+    pub(crate) current: Option<BufWriter<File>>,
+    count: usize,
+    used: u64,
+}
+
+impl<F: FnMut(usize) -> String> PartWriter<F> {
+    pub(crate) fn new(part_path: F) -> Self {
+        PartWriter {
+            part_path,
+            created: Vec::new(),
+            current: None,
+            count: 0,
+            used: 0,
+        }
+    }
+
+    pub(crate) fn start_part(&mut self, opening: &[u8]) -> Result<(), String> {
+        let path = (self.part_path)(self.created.len());
+        let f = File::create(&path)
+            .map_err(|e| format!("Cannot create output file '{}': {}", path, e))?;
+        self.created.push(path);
+
+        let mut writer = BufWriter::new(f);
+        writer
+            .write_all(opening)
+            .map_err(|e| format!("Write error: {}", e))?;
+        self.current = Some(writer);
+        self.count = 0;
+        self.used = 0;
+        Ok(())
+    }
+
+    pub(crate) fn finish_part(&mut self, closing: &[u8]) -> Result<(), String> {
+        if let Some(mut writer) = self.current.take() {
+            writer
+                .write_all(closing)
+                .and_then(|_| writer.flush())
+                .map_err(|e| format!("Disk save error: {}", e))?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn cleanup(&mut self) {
+        self.current = None;
+        for path in &self.created {
+            if let Err(e) = fs::remove_file(path) {
+                eprintln!("Warning: Could not cleanup partial file {}: {}", path, e);
+            }
+        }
+    }
+}
+
+fn validate_record(bytes: &[u8], what: &str) -> Result<(), String> {
+    serde_json::from_slice::<IgnoredAny>(bytes)
+        .map(|_| ())
+        .map_err(|e| format!("Invalid JSON in {}: {}", what, e))
+}
+
+// =========================================================================
+// JSON LINES
+// One record per line; blank lines are skipped.
+// =========================================================================
+
+/// Splits a JSON Lines (`.jsonl` / `.ndjson`) file, validating every record.
+///
+/// `part_path` receives the 0-based part index and returns the file to write.
+/// On error, every part created so far is removed.
+pub fn split_json_lines<P, F>(
+    input_path: P,
+    limit: JsonChunkLimit,
+    part_path: F,
+) -> Result<String, String>
+where
+    P: AsRef<Path>,
+    F: FnMut(usize) -> String,
+{
+    limit.validate()?;
+    let input_file = File::open(input_path.as_ref()).map_err(|e| format!("Open error: {}", e))?;
+
+    let mut parts = PartWriter::new(part_path);
+    let result = write_json_lines(BufReader::new(input_file), limit, &mut parts);
+    if result.is_err() {
+        parts.cleanup();
+    }
+    let records = result?;
+
+    Ok(format!(
+        "Success! Wrote {} records into {} parts.",
+        records,
+        parts.created.len()
+    ))
+}
+
+fn write_json_lines<F: FnMut(usize) -> String>(
+    reader: BufReader<File>,
+    limit: JsonChunkLimit,
+    parts: &mut PartWriter<F>,
+) -> Result<usize, String> {
+    let mut records = 0;
+
+    for (index, line_result) in reader.lines().enumerate() {
+        let line = line_result.map_err(|e| format!("Read error at line {}: {}", index + 1, e))?;
+        // A BOM can only start the first line
+        let line = if index == 0 {
+            line.trim_start_matches('\u{feff}')
+        } else {
+            &line
+        };
+        let record = line.trim();
+        if record.is_empty() {
+            continue;
+        }
+        validate_record(record.as_bytes(), &format!("line {}", index + 1))?;
+
+        let size = record.len() as u64 + 1;
+        if parts.current.is_none() || !limit.fits(parts.count, parts.used, size) {
+            parts.finish_part(b"")?;
+            parts.start_part(b"")?;
+        }
+        if let Some(writer) = parts.current.as_mut() {
+            writeln!(writer, "{}", record).map_err(|e| format!("Write error: {}", e))?;
+        }
+        parts.count += 1;
+        parts.used += size;
+        records += 1;
+    }
+    parts.finish_part(b"")?;
+
+    if records == 0 {
+        return Err("No JSON records found.".to_string());
+    }
+    Ok(records)
+}
+
+// =========================================================================
+// JSON ARRAY
+// Streams the elements of a top-level array without loading the whole
+// document, and writes each part as a valid array of its own.
+// =========================================================================
+
+/// Splits a file whose top-level value is a JSON array into smaller arrays.
+///
+/// Elements are copied byte-for-byte (pretty-printing inside an element is
+/// kept) and validated one by one. `part_path` works as in
+/// [`split_json_lines`].
+pub fn split_json_array<P, F>(
+    input_path: P,
+    limit: JsonChunkLimit,
+    part_path: F,
+) -> Result<String, String>
+where
+    P: AsRef<Path>,
+    F: FnMut(usize) -> String,
+{
+    limit.validate()?;
+    let input_file = File::open(input_path.as_ref()).map_err(|e| format!("Open error: {}", e))?;
+
+    let mut parts = PartWriter::new(part_path);
+    let result = write_json_array(BufReader::new(input_file), limit, &mut parts);
+    if result.is_err() {
+        parts.cleanup();
+    }
+    let records = result?;
+
+    Ok(format!(
+        "Success! Wrote {} elements into {} parts.",
+        records,
+        parts.created.len()
+    ))
+}
+
+fn write_json_array<R: BufRead, F: FnMut(usize) -> String>(
+    reader: R,
+    limit: JsonChunkLimit,
+    parts: &mut PartWriter<F>,
+) -> Result<usize, String> {
+    let mut scanner = ArrayScanner::new(reader);
+    scanner.expect_array_start()?;
+
+    let mut records = 0;
+    while let Some(element) = scanner.next_element()? {
+        validate_record(&element, &format!("array element {}", records + 1))?;
+
+        let size = element.len() as u64 + 2;
+        if parts.current.is_none() || !limit.fits(parts.count, parts.used, size) {
+            parts.finish_part(b"\n]\n")?;
+            parts.start_part(b"[\n")?;
+        }
+        if let Some(writer) = parts.current.as_mut() {
+            let separator: &[u8] = if parts.count == 0 { b"  " } else { b",\n  " };
+            writer
+                .write_all(separator)
+                .and_then(|_| writer.write_all(&element))
+                .map_err(|e| format!("Write error: {}", e))?;
+        }
+        parts.count += 1;
+        parts.used += size;
+        records += 1;
+    }
+    parts.finish_part(b"\n]\n")?;
+
+    if records == 0 {
+        return Err("JSON array is empty.".to_string());
+    }
+    Ok(records)
+}
+
+/// Byte-level walker over a top-level JSON array. It only tracks strings and
+/// nesting depth to find element boundaries; each element is then validated
+/// with `serde_json`.
+struct ArrayScanner<R: BufRead> {
+    reader: R,
+    offset: u64,
+    finished: bool,
+    /// Byte offset of the `,` ending the last element, if one did.
+    separator_at: Option<u64>,
+}
+
+impl<R: BufRead> ArrayScanner<R> {
+    fn new(reader: R) -> Self {
+        ArrayScanner {
+            reader,
+            offset: 0,
+            finished: false,
+            separator_at: None,
+        }
+    }
+
+    fn peek_byte(&mut self) -> Result<Option<u8>, String> {
+        let buffer = self
+            .reader
+            .fill_buf()
+            .map_err(|e| format!("Read error at byte {}: {}", self.offset, e))?;
+        Ok(buffer.first().copied())
+    }
+
+    fn next_byte(&mut self) -> Result<Option<u8>, String> {
+        let byte = self.peek_byte()?;
+        if byte.is_some() {
+            self.reader.consume(1);
+            self.offset += 1;
+        }
+        Ok(byte)
+    }
+
+    /// Next byte that is not JSON whitespace.
+    fn next_significant(&mut self) -> Result<Option<u8>, String> {
+        while let Some(b) = self.next_byte()? {
+            if !matches!(b, b' ' | b'\t' | b'\n' | b'\r') {
+                return Ok(Some(b));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Skips a UTF-8 BOM, if the input starts with one, and the opening `[`.
+    fn expect_array_start(&mut self) -> Result<(), String> {
+        const BOM: &[u8] = b"\xEF\xBB\xBF";
+        let buffer = self
+            .reader
+            .fill_buf()
+            .map_err(|e| format!("Read error at byte 0: {}", e))?;
+        if buffer.starts_with(BOM) {
+            self.reader.consume(BOM.len());
+            self.offset += BOM.len() as u64;
+        }
+        match self.next_significant()? {
+            Some(b'[') => Ok(()),
+            Some(_) => Err("Top-level JSON value is not an array.".to_string()),
+            None => Err("Input file is empty.".to_string()),
+        }
+    }
+
+    /// Raw bytes of the next element, or `None` after the closing `]`.
+    fn next_element(&mut self) -> Result<Option<Vec<u8>>, String> {
+        if self.finished {
+            return Ok(None);
+        }
+
+        let first = match self.next_significant()? {
+            Some(b']') => match self.separator_at {
+                Some(at) => return Err(format!("Trailing comma at byte {}", at)),
+                None => return self.finish().map(|_| None),
+            },
+            Some(b) => b,
+            None => return Err("Unexpected end of file inside the array.".to_string()),
+        };
+
+        let mut element = vec![first];
+        let mut depth: usize = 0;
+        let mut in_string = false;
+        let mut escaped = false;
+        let mut current = first;
+
+        loop {
+            if in_string {
+                if escaped {
+                    escaped = false;
+                } else if current == b'\\' {
+                    escaped = true;
+                } else if current == b'"' {
+                    in_string = false;
+                }
+            } else {
+                match current {
+                    b'"' => in_string = true,
+                    b'{' | b'[' => depth += 1,
+                    b'}' | b']' => {
+                        depth = depth
+                            .checked_sub(1)
+                            .ok_or_else(|| format!("Unbalanced brackets at byte {}", self.offset))?
+                    }
+                    _ => {}
+                }
+            }
+
+            // At depth 0 and outside a string, a ',' or ']' ends the element
+            if depth == 0 && !in_string {
+                match self.peek_byte()? {
+                    Some(b',') => {
+                        self.separator_at = Some(self.offset);
+                        self.next_byte()?;
+                        break;
+                    }
+                    Some(b']') => {
+                        self.separator_at = None;
+                        self.next_byte()?;
+                        self.finish()?;
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+
+            current = match self.next_byte()? {
+                Some(b) => b,
+                None => return Err("Unexpected end of file inside the array.".to_string()),
+            };
+            element.push(current);
+        }
+
+        while element.last().is_some_and(|b| b.is_ascii_whitespace()) {
+            element.pop();
+        }
+        Ok(Some(element))
+    }
+
+    /// Checks that nothing but whitespace follows the closing `]`.
+    fn finish(&mut self) -> Result<(), String> {
+        self.finished = true;
+        match self.next_significant()? {
+            None => Ok(()),
+            Some(_) => Err(format!(
+                "Unexpected data after the closing ']' at byte {}",
+                self.offset
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn elements(input: &str) -> Result<Vec<String>, String> {
+        let mut scanner = ArrayScanner::new(input.as_bytes());
+        scanner.expect_array_start()?;
+        let mut elements = Vec::new();
+        while let Some(element) = scanner.next_element()? {
+            elements.push(String::from_utf8(element).unwrap());
+        }
+        Ok(elements)
+    }
+
+    #[test]
+    fn nested_brackets_and_strings() {
+        let input = r#"[ {"a": [1, {"b": "]"}]}, [[], {}],
+            "x\"],[y", 3 ]"#;
+        assert_eq!(
+            elements(input).unwrap(),
+            [r#"{"a": [1, {"b": "]"}]}"#, "[[], {}]", r#""x\"],[y""#, "3"]
+        );
+    }
+
+    #[test]
+    fn escaped_backslash_before_quote() {
+        assert_eq!(elements(r#"["a\\", "b"]"#).unwrap(), [r#""a\\""#, r#""b""#]);
+    }
+
+    #[test]
+    fn empty_array() {
+        assert_eq!(elements(" [ \n ] ").unwrap(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn bom_only_at_the_start() {
+        assert_eq!(elements("\u{feff}[1, 2]").unwrap(), ["1", "2"]);
+        // Anywhere else it is data, not whitespace
+        assert!(elements("[1, \u{feff}2]").is_ok_and(|e| e[1] == "\u{feff}2"));
+        assert_eq!(
+            elements("\u{feff}\u{feff}[1]"),
+            Err("Top-level JSON value is not an array.".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_trailing_comma() {
+        assert_eq!(
+            elements("[1, 2,\n]"),
+            Err("Trailing comma at byte 5".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_data_after_the_array() {
+        assert_eq!(
+            elements("[1] [2]"),
+            Err("Unexpected data after the closing ']' at byte 5".to_string())
+        );
+        assert_eq!(
+            elements("[] x"),
+            Err("Unexpected data after the closing ']' at byte 4".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_truncated_input() {
+        let truncated = Err("Unexpected end of file inside the array.".to_string());
+        assert_eq!(elements("[1, {\"a\": 2"), truncated);
+        assert_eq!(elements("["), truncated);
+        assert_eq!(elements(""), Err("Input file is empty.".to_string()));
+        assert!(
+            elements("[1}]")
+                .unwrap_err()
+                .starts_with("Unbalanced brackets")
+        );
+    }
+
+    #[test]
+    fn byte_limit_keeps_oversized_elements_whole() {
+        let dir = std::env::temp_dir().join(format!("file_spliter_json_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let big = format!("\"{}\"", "x".repeat(50));
+        let input = format!("[1, {}, 2, 3]", big);
+
+        let mut parts = PartWriter::new(|i| dir.join(format!("{}.json", i)).display().to_string());
+        let records = write_json_array(input.as_bytes(), JsonChunkLimit::Bytes(10), &mut parts);
+        let written: Vec<String> = parts
+            .created
+            .iter()
+            .map(|path| fs::read_to_string(path).unwrap())
+            .collect();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(records, Ok(4));
+        assert_eq!(
+            written,
+            [
+                "[\n  1\n]\n",
+                &format!("[\n  {}\n]\n", big),
+                "[\n  2,\n  3\n]\n"
+            ]
+        );
+    }
+}
@@ -1,11 +1,66 @@
+#[cfg(feature = "async")]
+mod async_split;
+mod index;
+mod json;
+mod markdown;
+mod memory;
+#[cfg(feature = "parallel")]
+mod parallel;
+mod plan;
+mod prefix;
+mod progress;
+mod records;
+mod text;
+mod transform;
+
+#[cfg(feature = "async")]
+pub use async_split::split_file_async;
+pub use index::LineIndex;
+pub use json::{JsonChunkLimit, split_json_array, split_json_lines};
+pub use markdown::{MarkdownSection, scan_markdown_reader, scan_markdown_sections};
+pub use memory::{SplitParts, split_bytes, split_parts, split_str};
+pub use plan::{
+    PartNames, PartNaming, SplitMode, SplitPlan, SplitPlanner, parse_json_limit, parse_range,
+    parse_shares, parse_text_size, validate_input_path,
+};
+pub use prefix::LinePrefix;
+pub use progress::{CancelToken, ProgressCallback, SplitProgress};
+pub use records::{RecordTracker, SplitUnit};
+pub use text::{TextChunkSize, split_text};
+pub use transform::{
+    CollapseBlankLines, LineTransform, RegexFilter, SharedTransform, TrimWhitespace,
+};
+
+use prefix::Prefixer;
+use progress::ProgressTracker;
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use std::fmt;
 use std::fs::{self, File};
-use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::io::{BufRead, BufReader, BufWriter, Seek, SeekFrom, Write};
 use std::path::Path;
+use std::sync::MutexGuard;
 #[derive(Debug)]
 pub struct SplitConfig {
     pub start: usize,
     pub end: usize,
     pub output_path: String,
+    /// Lines written at the top of the part before its range, e.g. a
+    /// repeated CSV header.
+    pub preamble: Vec<String>,
+    /// Takes only every n-th record of the range instead of all of it.
+    pub interleave: Option<Interleave>,
+}
+
+/// Round-robin share of a range: counting records from the start of the
+/// range, a part takes those where `record % every == offset`. What a record
+/// is comes from [`SplitOptions::record_unit`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Interleave {
+    pub every: usize,
+    pub offset: usize,
 }
 
 impl SplitConfig {
@@ -26,8 +81,70 @@ impl SplitConfig {
             start,
             end,
             output_path,
+            preamble: Vec::new(),
+            interleave: None,
         })
     }
+
+    /// Sets the lines written before this part's range.
+    pub fn with_preamble(mut self, preamble: Vec<String>) -> Self {
+        self.preamble = preamble;
+        self
+    }
+
+    /// Makes this part take records `offset`, `offset + every`, ... of its
+    /// range, dealing the range out round-robin with the other parts.
+    pub fn with_interleave(mut self, every: usize, offset: usize) -> Result<Self, String> {
+        if offset >= every {
+            return Err(format!(
+                "Logic Error: Turn {} of {} for {}",
+                offset + 1,
+                every,
+                self.output_path
+            ));
+        }
+        self.interleave = Some(Interleave { every, offset });
+        Ok(self)
+    }
+}
+
+/// Extra behaviour for [`split_file_with_options`].
+/// The default options reproduce plain [`split_file`].
+#[derive(Clone, Default)]
+pub struct SplitOptions {
+    /// Shuffle the lines covered by the parts with this seed before they are
+    /// dealt out, so the same seed always produces the same parts.
+    pub shuffle_seed: Option<u64>,
+    /// Receives progress reports while the input is processed.
+    pub progress: Option<ProgressCallback>,
+    /// Aborts the split when cancelled.
+    pub cancel: Option<CancelToken>,
+    /// Writes each line's original line number or cue time in front of it.
+    pub line_prefix: Option<LinePrefix>,
+    /// Applied in order to every line read; a line dropped by one is not
+    /// written anywhere.
+    pub transforms: Vec<SharedTransform>,
+    /// What counts as one turn for parts with an [`Interleave`].
+    pub record_unit: SplitUnit,
+    /// Copy the parts concurrently from a memory-mapped input instead of
+    /// reading it line by line. Needs the `parallel` feature; shuffled
+    /// splits, files with `\r\n` line endings and files that are not valid
+    /// UTF-8 always use the line-by-line path.
+    pub parallel: bool,
+}
+
+impl fmt::Debug for SplitOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SplitOptions")
+            .field("shuffle_seed", &self.shuffle_seed)
+            .field("progress", &self.progress.is_some())
+            .field("cancel", &self.cancel)
+            .field("line_prefix", &self.line_prefix)
+            .field("transforms", &self.transforms.len())
+            .field("record_unit", &self.record_unit)
+            .field("parallel", &self.parallel)
+            .finish()
+    }
 }
 
 // =========================================================================
@@ -49,7 +166,8 @@ fn validate_file(path: &Path) -> Result<(), String> {
 
 // =========================================================================
 // HELPER 2: CREATE WRITERS
-// Opens all output files at once and prepares them for writing.
+// Opens all output files at once, writes each preamble and prepares them
+// for writing.
 // =========================================================================
 fn create_writers(parts: &[SplitConfig]) -> Result<Vec<BufWriter<File>>, String> {
     let mut writers = Vec::new();
@@ -57,50 +175,263 @@ fn create_writers(parts: &[SplitConfig]) -> Result<Vec<BufWriter<File>>, String>
     for part in parts {
         let f = File::create(&part.output_path)
             .map_err(|e| format!("Cannot create output file '{}': {}", part.output_path, e))?;
-        writers.push(BufWriter::new(f));
+        let mut writer = BufWriter::new(f);
+        write_preamble(&mut writer, part)?;
+        writers.push(writer);
     }
 
     Ok(writers)
 }
 
+fn write_preamble<W: Write>(writer: &mut W, part: &SplitConfig) -> Result<(), String> {
+    for line in &part.preamble {
+        writeln!(writer, "{}", line)
+            .map_err(|e| format!("Write error to '{}': {}", part.output_path, e))?;
+    }
+    Ok(())
+}
+
 // =========================================================================
 // HELPER 3: CORE PROCESSING LOOP
-// Reads input line-by-line and writes to the correct output(s).
-// Returns the total number of lines read.
+// Reads input line-by-line and writes to the correct output(s). Lines
+// dropped by a transform arrive without text and still count.
+// `first_line` is the number of the first line the iterator yields (1
+// unless the reader was moved with a line index).
+// Returns the number of the last line read.
 // =========================================================================
-fn process_lines(
-    reader: BufReader<File>,
+fn process_lines<I, W>(
+    lines: I,
+    first_line: usize,
     parts: &[SplitConfig],
-    mut writers: Vec<BufWriter<File>>,
-) -> Result<usize, String> {
-    let mut total_lines = 0;
+    writers: &mut [W],
+    tracker: &mut ProgressTracker,
+) -> Result<usize, String>
+where
+    I: Iterator<Item = std::io::Result<PreparedLine>>,
+    W: Write,
+{
+    let mut total_lines = first_line - 1;
+    // Record each interleaved part's range starts at
+    let mut bases: Vec<Option<usize>> = vec![None; parts.len()];
 
-    for (index, line_result) in reader.lines().enumerate() {
+    for (index, line_result) in lines.enumerate() {
+        let current_line = first_line + index;
         // Read the line safely
-        let line = line_result.map_err(|e| format!("Read error at line {}: {}", index + 1, e))?;
-        let current_line = index + 1;
+        let line =
+            line_result.map_err(|e| format!("Read error at line {}: {}", current_line, e))?;
         total_lines = current_line;
 
         // Check which file needs this line
+        let mut written_to = None;
         for (i, config) in parts.iter().enumerate() {
-            if current_line >= config.start && current_line <= config.end {
+            if current_line < config.start || current_line > config.end {
+                continue;
+            }
+            if let Some(turn) = &config.interleave {
+                let base = *bases[i].get_or_insert(line.record);
+                if (line.record - base) % turn.every != turn.offset {
+                    continue;
+                }
+            }
+            if let Some(text) = &line.text {
                 // Write to the specific writer
-                writeln!(writers[i], "{}", line)
+                writeln!(writers[i], "{}", text)
                     .map_err(|e| format!("Write error to '{}': {}", config.output_path, e))?;
+                written_to = Some(i + 1);
             }
         }
+
+        tracker.line_done(line.input_bytes, written_to)?;
     }
+    tracker.check_cancelled()?;
 
     // Flush all buffers to disk to ensure data is saved
-    for mut w in writers {
+    for w in writers.iter_mut() {
         w.flush().map_err(|e| format!("Disk save error: {}", e))?;
     }
 
+    tracker.finish();
     Ok(total_lines)
 }
 
 // =========================================================================
-// HELPER 4: CLEANUP
+// HELPER 4: SHUFFLE
+// Loads the whole input and shuffles the lines the parts cover, leaving
+// anything before the first part (e.g. a header) in place.
+// =========================================================================
+fn read_shuffled<I>(
+    input: I,
+    parts: &[SplitConfig],
+    seed: u64,
+    tracker: &ProgressTracker,
+) -> Result<Vec<PreparedLine>, String>
+where
+    I: Iterator<Item = std::io::Result<PreparedLine>>,
+{
+    let mut lines = Vec::new();
+    for (index, line_result) in input.enumerate() {
+        if index.is_multiple_of(progress::REPORT_EVERY) {
+            tracker.check_cancelled()?;
+        }
+        lines.push(line_result.map_err(|e| format!("Read error at line {}: {}", index + 1, e))?);
+    }
+
+    let first = parts.iter().map(|p| p.start).min().unwrap_or(1);
+    let last = parts
+        .iter()
+        .map(|p| p.end)
+        .max()
+        .unwrap_or(0)
+        .min(lines.len());
+    if first <= last {
+        let mut rng = StdRng::seed_from_u64(seed);
+        lines[first - 1..last].shuffle(&mut rng);
+    }
+
+    Ok(lines)
+}
+
+// =========================================================================
+// HELPER 5: LINE PREPARATION
+// Tags lines with their record, runs the transforms and adds provenance as
+// lines are read, before any shuffling, so each line keeps the record,
+// number or cue time it had in the input.
+// =========================================================================
+struct PreparedLine {
+    /// Record the original line belongs to (see [`RecordTracker`]).
+    record: usize,
+    /// `None` if a transform dropped the line.
+    text: Option<String>,
+    /// Length of the line as read, before any prefix or transform; progress
+    /// is measured against the input's size.
+    input_bytes: usize,
+}
+
+fn prepare_lines<'a, I>(
+    lines: I,
+    first_line: usize,
+    mut records: RecordTracker,
+    mut prefixer: Option<Prefixer>,
+    mut transforms: Vec<MutexGuard<'a, dyn LineTransform + 'static>>,
+) -> impl Iterator<Item = std::io::Result<PreparedLine>>
+where
+    I: Iterator<Item = std::io::Result<String>>,
+{
+    lines.enumerate().map(move |(index, line_result)| {
+        let line_number = first_line + index;
+        line_result.map(|line| {
+            let input_bytes = line.len();
+            records.starts_record(&line);
+            let prefix = prefixer.as_mut().and_then(|p| p.prefix(line_number, &line));
+            let text = transforms
+                .iter_mut()
+                .try_fold(line, |line, t| t.transform(line_number, line))
+                .map(|line| match prefix {
+                    Some(prefix) => prefix + &line,
+                    None => line,
+                });
+            PreparedLine {
+                record: records.record(),
+                text,
+                input_bytes,
+            }
+        })
+    })
+}
+
+// =========================================================================
+// HELPER 6: PIPELINE
+// Everything between opening the input and checking the ranges, shared by
+// the file and in-memory splits. `plan` is every part of the split (it
+// decides what is shuffled and how wide line numbers are); `parts` are the
+// ones actually written, each to the writer `create` returns for it.
+// Returns the number of lines in the input and the writers.
+// =========================================================================
+struct LineSource<R> {
+    reader: R,
+    /// Number of the first line `reader` yields (1 unless it was moved with
+    /// a line index).
+    first_line: usize,
+    skipped_bytes: u64,
+    total_bytes: u64,
+    index: Option<LineIndex>,
+}
+
+fn run_split<R, W, F>(
+    source: LineSource<R>,
+    plan: &[SplitConfig],
+    parts: &[SplitConfig],
+    create: F,
+    options: &SplitOptions,
+) -> Result<(usize, Vec<W>), String>
+where
+    R: BufRead,
+    W: Write,
+    F: FnOnce(&[SplitConfig]) -> Result<Vec<W>, String>,
+{
+    let LineSource {
+        reader,
+        first_line,
+        skipped_bytes,
+        total_bytes,
+        index,
+    } = source;
+
+    let last = plan.iter().map(|p| p.end).max().unwrap_or(0);
+    let prefixer = options.line_prefix.map(|kind| {
+        let last_line = index.as_ref().map_or(last, |i| last.min(i.total_lines()));
+        Prefixer::new(kind, last_line)
+    });
+    let transforms = options
+        .transforms
+        .iter()
+        .map(|t| {
+            t.lock()
+                .map_err(|_| "A line transform panicked earlier.".to_string())
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let records = RecordTracker::new(options.record_unit);
+    let lines = prepare_lines(reader.lines(), first_line, records, prefixer, transforms);
+
+    let mut writers = create(parts)?;
+
+    let mut tracker = ProgressTracker::new(
+        options.progress.as_ref(),
+        options.cancel.as_ref(),
+        total_bytes,
+    );
+    tracker.skip(first_line - 1, skipped_bytes);
+    let total_lines = match (options.shuffle_seed, &index) {
+        (Some(seed), _) => {
+            let lines = read_shuffled(lines, plan, seed, &tracker)?;
+            process_lines(
+                lines.into_iter().map(Ok),
+                1,
+                parts,
+                &mut writers,
+                &mut tracker,
+            )?
+        }
+        (None, Some(index)) => {
+            let last = parts.iter().map(|p| p.end).max().unwrap_or(0);
+            let needed = (last + 1).saturating_sub(first_line);
+            process_lines(
+                lines.take(needed),
+                first_line,
+                parts,
+                &mut writers,
+                &mut tracker,
+            )?;
+            index.total_lines()
+        }
+        (None, None) => process_lines(lines, 1, parts, &mut writers, &mut tracker)?,
+    };
+
+    Ok((total_lines, writers))
+}
+
+// =========================================================================
+// HELPER 7: CLEANUP
 // Checks if any file turned out empty because the input was too short.
 // =========================================================================
 fn verify_and_cleanup(parts: &[SplitConfig], total_lines: usize) -> Result<(), String> {
@@ -108,11 +439,8 @@ fn verify_and_cleanup(parts: &[SplitConfig], total_lines: usize) -> Result<(), S
 
     for part in parts {
         // If the file ended before this part even started
-        if total_lines < part.start {
-            errors.push(format!(
-                "❌ Range {}-{} failed: Input file only has {} lines.",
-                part.start, part.end, total_lines
-            ));
+        if let Some(error) = short_range_error(part, total_lines) {
+            errors.push(error);
 
             // Delete the empty garbage file
             if let Err(e) = fs::remove_file(&part.output_path) {
@@ -131,25 +459,95 @@ fn verify_and_cleanup(parts: &[SplitConfig], total_lines: usize) -> Result<(), S
     Ok(())
 }
 
+/// The error for a part whose range starts after the last line of the input.
+fn short_range_error(part: &SplitConfig, total_lines: usize) -> Option<String> {
+    (total_lines < part.start).then(|| {
+        format!(
+            "❌ Range {}-{} failed: Input file only has {} lines.",
+            part.start, part.end, total_lines
+        )
+    })
+}
+
+fn remove_outputs<'a>(parts: impl IntoIterator<Item = &'a SplitConfig>) {
+    for part in parts {
+        if let Err(e) = fs::remove_file(&part.output_path) {
+            eprintln!(
+                "Warning: Could not cleanup partial file {}: {}",
+                part.output_path, e
+            );
+        }
+    }
+}
+
 // =========================================================================
 // MAIN PUBLIC FUNCTION
 // Now acts as a simple "Coordinator" calling the steps above.
 // =========================================================================
 pub fn split_file<P: AsRef<Path>>(input_path: P, parts: &[SplitConfig]) -> Result<String, String> {
+    split_file_with_options(input_path, parts, SplitOptions::default())
+}
+
+/// Same as [`split_file`], with the extra behaviour described by `options`.
+pub fn split_file_with_options<P: AsRef<Path>>(
+    input_path: P,
+    parts: &[SplitConfig],
+    options: SplitOptions,
+) -> Result<String, String> {
     let path_ref = input_path.as_ref();
 
     // Step 1: Validate Input
     validate_file(path_ref)?;
 
+    #[cfg(feature = "parallel")]
+    if options.parallel
+        && let Some(summary) = parallel::split_mapped(path_ref, parts, &options)?
+    {
+        return Ok(summary);
+    }
+
     // Step 2: Open Input Reader
-    let input_file = File::open(path_ref).map_err(|e| format!("Open error: {}", e))?;
-    let reader = BufReader::new(input_file);
+    let mut input_file = File::open(path_ref).map_err(|e| format!("Open error: {}", e))?;
+
+    // A valid line index lets a plain split skip straight to the first line
+    // it needs and stop after the last one. Cue prefixes need to see every
+    // timing line, records every line before theirs (a seek could land in a
+    // quoted CSV field) and transforms every line they may keep state on,
+    // so those always read from the top.
+    let seekable = options.shuffle_seed.is_none()
+        && options.line_prefix != Some(LinePrefix::CueStart)
+        && options.record_unit == SplitUnit::Lines
+        && options.transforms.is_empty();
+    let index = if seekable {
+        LineIndex::load(path_ref).ok().flatten()
+    } else {
+        None
+    };
+    let mut first_line = 1;
+    let mut skipped_bytes = 0;
+    if let Some(index) = &index {
+        let wanted = parts.iter().map(|p| p.start).min().unwrap_or(1);
+        (first_line, skipped_bytes) = index.seek_point(wanted);
+        input_file
+            .seek(SeekFrom::Start(skipped_bytes))
+            .map_err(|e| format!("Seek error: {}", e))?;
+    }
+    let source = LineSource {
+        reader: BufReader::new(input_file),
+        first_line,
+        skipped_bytes,
+        total_bytes: fs::metadata(path_ref).map(|m| m.len()).unwrap_or(0),
+        index,
+    };
 
-    // Step 3: Prepare Output Writers
-    let writers = create_writers(parts)?;
+    // Steps 3 & 4: Prepare Output Writers and Run the Processing Loop
+    let result = run_split(source, parts, parts, create_writers, &options);
 
-    // Step 4: Run the Processing Loop
-    let total_lines = process_lines(reader, parts, writers)?;
+    // A cancelled split leaves no half-written parts behind
+    if result.is_err() && options.cancel.as_ref().is_some_and(|c| c.is_cancelled()) {
+        remove_outputs(parts);
+    }
+    let (total_lines, _) = result?;
 
     // Step 5: Post-Process Verification
     verify_and_cleanup(parts, total_lines)?;
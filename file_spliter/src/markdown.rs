@@ -0,0 +1,253 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// A slice of a Markdown document that starts at a heading.
+#[derive(Debug, Clone)]
+pub struct MarkdownSection {
+    /// First line of the section (1-based, inclusive).
+    pub start: usize,
+    /// Last line of the section (1-based, inclusive).
+    pub end: usize,
+    /// Heading text without the `#` markers; `None` for the text that comes
+    /// before the first heading.
+    pub title: Option<String>,
+    /// Full heading lines of the sections this one is nested in, outermost
+    /// first.
+    pub parents: Vec<String>,
+}
+
+/// Finds the sections of a Markdown file, cutting at every ATX heading
+/// (`#`, `##`, ...) of `level` or above. Headings inside fenced code blocks
+/// are ignored, so a section never ends in the middle of a fence.
+pub fn scan_markdown_sections<P: AsRef<Path>>(
+    input_path: P,
+    level: usize,
+) -> Result<Vec<MarkdownSection>, String> {
+    let input_file = File::open(input_path.as_ref()).map_err(|e| format!("Open error: {}", e))?;
+    scan_markdown_reader(BufReader::new(input_file), level)
+}
+
+/// Same as [`scan_markdown_sections`], for a document that is not in a file
+/// (e.g. a byte slice).
+pub fn scan_markdown_reader<R: BufRead>(
+    reader: R,
+    level: usize,
+) -> Result<Vec<MarkdownSection>, String> {
+    if !(1..=6).contains(&level) {
+        return Err(format!("Heading level must be 1-6 (got {}).", level));
+    }
+
+    let mut sections: Vec<MarkdownSection> = Vec::new();
+    // Open headings above the split level, indexed by level - 1
+    let mut open_headings: Vec<Option<String>> = vec![None; 6];
+    let mut fence: Option<(char, usize)> = None;
+    let mut has_preface_text = false;
+    let mut total_lines = 0;
+
+    for (index, line_result) in reader.lines().enumerate() {
+        let line = line_result.map_err(|e| format!("Read error at line {}: {}", index + 1, e))?;
+        let current_line = index + 1;
+        total_lines = current_line;
+
+        // 1. Code fences hide everything until they are closed
+        if let Some(marker) = fence_marker(&line) {
+            match fence {
+                None => fence = Some(marker),
+                Some((ch, len)) if marker.0 == ch && marker.1 >= len => fence = None,
+                Some(_) => {}
+            }
+            continue;
+        }
+        if fence.is_some() {
+            continue;
+        }
+
+        // 2. Headings
+        let Some((heading_level, text)) = parse_heading(&line) else {
+            if sections.is_empty() && !line.trim().is_empty() {
+                has_preface_text = true;
+            }
+            continue;
+        };
+
+        // Forget headings at this level or deeper, they are now closed
+        for slot in open_headings.iter_mut().skip(heading_level - 1) {
+            *slot = None;
+        }
+
+        if heading_level <= level {
+            if let Some(last) = sections.last_mut() {
+                last.end = current_line - 1;
+            } else if has_preface_text {
+                sections.push(MarkdownSection {
+                    start: 1,
+                    end: current_line - 1,
+                    title: None,
+                    parents: Vec::new(),
+                });
+            }
+
+            sections.push(MarkdownSection {
+                start: current_line,
+                end: current_line,
+                title: Some(text),
+                parents: open_headings.iter().flatten().cloned().collect(),
+            });
+        }
+
+        open_headings[heading_level - 1] = Some(line.trim_end().to_string());
+    }
+
+    if fence.is_some() {
+        return Err("Markdown ends inside an unclosed code fence.".to_string());
+    }
+
+    match sections.last_mut() {
+        Some(last) => last.end = total_lines,
+        None if total_lines > 0 => sections.push(MarkdownSection {
+            start: 1,
+            end: total_lines,
+            title: None,
+            parents: Vec::new(),
+        }),
+        None => return Err("Input file is empty.".to_string()),
+    }
+
+    Ok(sections)
+}
+
+/// Returns the fence character and length if the line opens or closes a
+/// fenced code block (at most three spaces of indentation).
+fn fence_marker(line: &str) -> Option<(char, usize)> {
+    let trimmed = line.trim_start_matches(' ');
+    if line.len() - trimmed.len() > 3 {
+        return None;
+    }
+    let ch = trimmed.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let len = trimmed.chars().take_while(|c| *c == ch).count();
+    (len >= 3).then_some((ch, len))
+}
+
+/// Returns the level and text of an ATX heading line.
+fn parse_heading(line: &str) -> Option<(usize, String)> {
+    let trimmed = line.trim_start_matches(' ');
+    if line.len() - trimmed.len() > 3 {
+        return None;
+    }
+    let level = trimmed.chars().take_while(|c| *c == '#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+
+    let rest = &trimmed[level..];
+    if !rest.is_empty() && !rest.starts_with([' ', '\t']) {
+        return None;
+    }
+
+    // Drop an optional closing sequence of #'s
+    let text = rest.trim();
+    let text = match text.trim_end_matches('#') {
+        stripped if stripped.is_empty() || stripped.ends_with([' ', '\t']) => stripped.trim_end(),
+        _ => text,
+    };
+    Some((level, text.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SplitMode, SplitPlanner, split_str};
+
+    /// `(start, end, title)` of every section.
+    fn sections(input: &str, level: usize) -> Vec<(usize, usize, Option<String>)> {
+        scan_markdown_reader(input.as_bytes(), level)
+            .unwrap()
+            .into_iter()
+            .map(|s| (s.start, s.end, s.title))
+            .collect()
+    }
+
+    fn title(text: &str) -> Option<String> {
+        Some(text.to_string())
+    }
+
+    #[test]
+    fn cuts_at_headings_up_to_level() {
+        let input = "intro\n# Top\ntext\n## One ##\n### Deep\n## Two\n#tag\n";
+        assert_eq!(
+            sections(input, 2),
+            [
+                (1, 1, None),
+                (2, 3, title("Top")),
+                (4, 5, title("One")),
+                (6, 7, title("Two")),
+            ]
+        );
+        assert_eq!(sections(input, 1), [(1, 1, None), (2, 7, title("Top"))]);
+    }
+
+    #[test]
+    fn headings_inside_fences_are_ignored() {
+        let input = "## A\n```md\n## not a heading\n~~~\n## still code\n```\n## B\n~~~~\n```\n## code\n~~~~\n";
+        assert_eq!(
+            sections(input, 2),
+            [(1, 6, title("A")), (7, 11, title("B"))]
+        );
+    }
+
+    #[test]
+    fn longer_fence_needs_a_longer_close() {
+        let input = "## A\n````\n```\n## code\n````\n## B\n";
+        assert_eq!(sections(input, 2), [(1, 5, title("A")), (6, 6, title("B"))]);
+    }
+
+    #[test]
+    fn unclosed_fence_is_an_error() {
+        let error = scan_markdown_reader("## A\n```\n## B\n".as_bytes(), 2).unwrap_err();
+        assert_eq!(error, "Markdown ends inside an unclosed code fence.");
+    }
+
+    #[test]
+    fn parents_are_the_open_headings() {
+        let input = "# Guide\n## Setup\n### Linux\n### Mac\n# Reference\n### Flags\n";
+        let found = scan_markdown_reader(input.as_bytes(), 3).unwrap();
+        let parents: Vec<Vec<String>> = found.into_iter().map(|s| s.parents).collect();
+        assert_eq!(
+            parents,
+            [
+                vec![],
+                vec!["# Guide".to_string()],
+                vec!["# Guide".to_string(), "## Setup".to_string()],
+                vec!["# Guide".to_string(), "## Setup".to_string()],
+                vec![],
+                vec!["# Reference".to_string()],
+            ]
+        );
+    }
+
+    #[test]
+    fn repeat_parents_in_parts() {
+        let input = "# Guide\nhello\n## Setup\n```\n# not a parent\n```\n## Use\nrun it\n";
+        let split = |repeat_parents| {
+            let mode = SplitMode::Markdown {
+                level: 2,
+                repeat_parents,
+            };
+            let plan = SplitPlanner::new("guide.md", mode)
+                .build_from_bytes(input.as_bytes())
+                .unwrap();
+            split_str(input, &plan.configs, plan.options).unwrap()
+        };
+
+        assert_eq!(
+            split(true),
+            [
+                "# Guide\nhello\n",
+                "# Guide\n\n## Setup\n```\n# not a parent\n```\n",
+                "# Guide\n\n## Use\nrun it\n",
+            ]
+        );
+        assert_eq!(split(false)[2], "## Use\nrun it\n");
+    }
+}
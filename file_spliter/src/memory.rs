@@ -0,0 +1,159 @@
+use crate::plan::PlanInput;
+use crate::{
+    LineSource, SplitConfig, SplitOptions, SplitPlan, SplitPlanner, run_split, short_range_error,
+    write_preamble,
+};
+use std::slice;
+
+impl SplitPlanner {
+    /// Same as [`build`](Self::build), but plans against `input` instead of
+    /// reading the input path, which then only names the parts. Pass the
+    /// plan on to [`split_bytes`] or [`split_parts`] with the same input.
+    ///
+    /// ```
+    /// use file_spliter::{SplitMode, SplitPlanner, SplitUnit, split_str};
+    ///
+    /// let input = "id,name\n1,a\n2,b\n3,c\n";
+    /// let mode = SplitMode::Auto { chunk_size: 2, unit: SplitUnit::CsvRecords };
+    /// let plan = SplitPlanner::new("people.csv", mode)
+    ///     .with_header_lines(1)
+    ///     .build_from_bytes(input.as_bytes())?;
+    /// let parts = split_str(input, &plan.configs, plan.options)?;
+    /// assert_eq!(parts, ["id,name\n1,a\n2,b\n", "id,name\n3,c\n"]);
+    /// # Ok::<(), String>(())
+    /// ```
+    pub fn build_from_bytes(&self, input: &[u8]) -> Result<SplitPlan, String> {
+        if input.is_empty() {
+            return Err("Input is empty (0 bytes).".to_string());
+        }
+        self.build_input(PlanInput::Bytes(input))
+    }
+}
+
+/// Same as [`split_file_with_options`](crate::split_file_with_options), but
+/// reads the input from memory and returns every part's contents instead of
+/// writing files, in the order of `parts`.
+///
+/// `output_path` is only used to name a part in error messages. The input
+/// must be UTF-8; `options.parallel` is ignored. Plan the parts with
+/// [`SplitPlanner::build_from_bytes`] to split without a file at all.
+pub fn split_bytes(
+    input: &[u8],
+    parts: &[SplitConfig],
+    options: SplitOptions,
+) -> Result<Vec<Vec<u8>>, String> {
+    if input.is_empty() {
+        return Err("Input is empty (0 bytes).".to_string());
+    }
+
+    let (total_lines, buffers) = run_split(source(input), parts, parts, buffers, &options)?;
+
+    let errors: Vec<String> = parts
+        .iter()
+        .filter_map(|part| short_range_error(part, total_lines))
+        .collect();
+    if !errors.is_empty() {
+        return Err(errors.join("\n"));
+    }
+    Ok(buffers)
+}
+
+/// [`split_bytes`] for text.
+pub fn split_str(
+    input: &str,
+    parts: &[SplitConfig],
+    options: SplitOptions,
+) -> Result<Vec<String>, String> {
+    split_bytes(input.as_bytes(), parts, options).map(|buffers| {
+        buffers
+            .into_iter()
+            // Lines were read as UTF-8, so the parts are too
+            .map(|buffer| String::from_utf8(buffer).unwrap_or_default())
+            .collect()
+    })
+}
+
+/// Lazy version of [`split_bytes`]: produces one part per `next()`, so only
+/// one part is held in memory at a time.
+pub fn split_parts<'a>(
+    input: &'a [u8],
+    parts: &'a [SplitConfig],
+    options: SplitOptions,
+) -> SplitParts<'a> {
+    SplitParts {
+        input,
+        parts,
+        options,
+        next: 0,
+    }
+}
+
+/// Iterator returned by [`split_parts`].
+///
+/// Every part is cut in its own pass over the input. The parts are the same
+/// as [`split_bytes`] returns, except that a stateful
+/// [`LineTransform`](crate::LineTransform) sees the input once per part, and
+/// progress reports restart for every part.
+#[derive(Debug)]
+pub struct SplitParts<'a> {
+    input: &'a [u8],
+    parts: &'a [SplitConfig],
+    options: SplitOptions,
+    next: usize,
+}
+
+impl Iterator for SplitParts<'_> {
+    type Item = Result<Vec<u8>, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let part = self.parts.get(self.next)?;
+        self.next += 1;
+        if self.input.is_empty() {
+            return Some(Err("Input is empty (0 bytes).".to_string()));
+        }
+
+        let result = run_split(
+            source(self.input),
+            self.parts,
+            slice::from_ref(part),
+            buffers,
+            &self.options,
+        )
+        .and_then(|(total_lines, mut buffers)| {
+            match short_range_error(part, total_lines) {
+                Some(error) => Err(error),
+                None => Ok(buffers.pop().unwrap_or_default()),
+            }
+        });
+        Some(result)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = self.parts.len() - self.next;
+        (left, Some(left))
+    }
+}
+
+impl ExactSizeIterator for SplitParts<'_> {}
+
+fn source(input: &[u8]) -> LineSource<&[u8]> {
+    LineSource {
+        reader: input,
+        first_line: 1,
+        skipped_bytes: 0,
+        total_bytes: input.len() as u64,
+        index: None,
+    }
+}
+
+/// One buffer per part, starting with its preamble.
+fn buffers(parts: &[SplitConfig]) -> Result<Vec<Vec<u8>>, String> {
+    parts
+        .iter()
+        .map(|part| {
+            let mut buffer = Vec::new();
+            write_preamble(&mut buffer, part)?;
+            Ok(buffer)
+        })
+        .collect()
+}
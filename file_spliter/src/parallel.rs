@@ -0,0 +1,412 @@
+use crate::{SplitConfig, SplitOptions, SplitProgress, remove_outputs};
+use memmap2::Mmap;
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+/// Bytes copied between two progress reports / cancellation checks.
+const COPY_BLOCK: usize = 1 << 20;
+
+/// Smallest slice of the input a worker counts newlines in.
+const MIN_SCAN_CHUNK: usize = 4 << 20;
+
+/// Splits by copying every part's byte range straight out of a memory map,
+/// one part per worker thread.
+///
+/// Returns `Ok(None)` when the input needs the line-by-line path instead:
+/// shuffled, prefixed, transformed or interleaved splits, files with `\r`
+/// characters (the sequential path rewrites `\r\n` to `\n`, a byte copy
+/// would not) and files that are not valid UTF-8 (so they fail with the
+/// same error as there).
+pub(crate) fn split_mapped(
+    path: &Path,
+    parts: &[SplitConfig],
+    options: &SplitOptions,
+) -> Result<Option<String>, String> {
+    if options.shuffle_seed.is_some()
+        || options.line_prefix.is_some()
+        || !options.transforms.is_empty()
+        || parts.iter().any(|p| p.interleave.is_some())
+    {
+        return Ok(None);
+    }
+
+    let input_file = File::open(path).map_err(|e| format!("Open error: {}", e))?;
+    // SAFETY: the map is only read, and the input is not expected to change
+    // while it is being split (the same assumption the index makes).
+    let data = unsafe { Mmap::map(&input_file) }.map_err(|e| format!("Map error: {}", e))?;
+
+    // Step 1: Count newlines per chunk and check the text, in parallel
+    let chunks = scan_chunks(&data);
+    let stats: Vec<(usize, bool)> = chunks
+        .par_iter()
+        .map(|&(start, end)| {
+            let slice = &data[start..end];
+            let copyable = !slice.contains(&b'\r') && std::str::from_utf8(slice).is_ok();
+            (slice.iter().filter(|b| **b == b'\n').count(), copyable)
+        })
+        .collect();
+    if stats.iter().any(|(_, copyable)| !copyable) {
+        return Ok(None);
+    }
+    let counts: Vec<usize> = stats.iter().map(|(count, _)| *count).collect();
+    let newlines: usize = counts.iter().sum();
+    let total_lines = match data.last() {
+        Some(b) if *b != b'\n' => newlines + 1,
+        _ => newlines,
+    };
+
+    // Step 2: Resolve the byte offset of every part boundary
+    let mut targets: Vec<usize> = parts
+        .iter()
+        .flat_map(|p| [p.start, p.end.saturating_add(1)])
+        .collect();
+    targets.sort_unstable();
+    targets.dedup();
+    let offsets = line_offsets(&data, &chunks, &counts, &targets);
+
+    // Step 3: Copy the parts concurrently
+    let in_range: Vec<(usize, &SplitConfig)> = parts
+        .iter()
+        .enumerate()
+        .filter(|(_, p)| p.start <= total_lines)
+        .collect();
+    let spans: Vec<(usize, usize)> = in_range
+        .iter()
+        .map(|(_, p)| (offsets[&p.start], offsets[&p.end.saturating_add(1)]))
+        .collect();
+    let to_copy: u64 = spans.iter().map(|(s, e)| (e - s) as u64).sum();
+
+    let copied = AtomicU64::new(0);
+    let failed = AtomicBool::new(false);
+    // Parts past the end, or not reached before a cancel, are never created
+    let created: Vec<AtomicBool> = parts.iter().map(|_| AtomicBool::new(false)).collect();
+    // The last block is left to the final report below
+    let report = |part: usize| {
+        let bytes_read = copied.load(Ordering::Relaxed);
+        if let Some(callback) = options.progress.as_ref().filter(|_| bytes_read < to_copy) {
+            callback(&SplitProgress {
+                bytes_read,
+                total_bytes: to_copy,
+                lines_read: 0,
+                current_part: Some(part),
+            });
+        }
+    };
+
+    let result: Result<(), String> =
+        in_range
+            .par_iter()
+            .zip(spans.par_iter())
+            .try_for_each(|(&(i, part), &(start, end))| {
+                let write = || -> Result<(), String> {
+                    let f = File::create(&part.output_path).map_err(|e| {
+                        format!("Cannot create output file '{}': {}", part.output_path, e)
+                    })?;
+                    created[i].store(true, Ordering::Relaxed);
+                    let mut writer = BufWriter::new(f);
+                    let write_error = |e| format!("Write error to '{}': {}", part.output_path, e);
+                    for line in &part.preamble {
+                        writeln!(writer, "{}", line).map_err(write_error)?;
+                    }
+
+                    for block in data[start..end].chunks(COPY_BLOCK) {
+                        if failed.load(Ordering::Relaxed) {
+                            return Ok(());
+                        }
+                        if options.cancel.as_ref().is_some_and(|c| c.is_cancelled()) {
+                            return Err("Split cancelled.".to_string());
+                        }
+                        writer.write_all(block).map_err(write_error)?;
+                        copied.fetch_add(block.len() as u64, Ordering::Relaxed);
+                        report(i + 1);
+                    }
+                    // The last line of the file may lack its newline
+                    if end > start && data[end - 1] != b'\n' {
+                        writer.write_all(b"\n").map_err(write_error)?;
+                    }
+                    writer
+                        .flush()
+                        .map_err(|e| format!("Disk save error: {}", e))
+                };
+                write().inspect_err(|_| failed.store(true, Ordering::Relaxed))
+            });
+
+    if result.is_err() && options.cancel.as_ref().is_some_and(|c| c.is_cancelled()) {
+        remove_outputs(
+            parts
+                .iter()
+                .zip(&created)
+                .filter(|(_, created)| created.load(Ordering::Relaxed))
+                .map(|(part, _)| part),
+        );
+    }
+    result?;
+
+    if let Some(callback) = &options.progress {
+        callback(&SplitProgress {
+            bytes_read: to_copy,
+            total_bytes: to_copy,
+            lines_read: total_lines,
+            current_part: None,
+        });
+    }
+
+    // Same report as the sequential path for ranges past the end
+    let errors: Vec<String> = parts
+        .iter()
+        .filter(|p| p.start > total_lines)
+        .map(|p| {
+            format!(
+                "❌ Range {}-{} failed: Input file only has {} lines.",
+                p.start, p.end, total_lines
+            )
+        })
+        .collect();
+    if !errors.is_empty() {
+        return Err(errors.join("\n"));
+    }
+
+    Ok(Some(format!("Success! Processed {} lines.", total_lines)))
+}
+
+/// Cuts `data` into roughly equal chunks, a few per worker thread. Every
+/// chunk but the last ends just after a newline, so no chunk starts inside
+/// a UTF-8 character.
+fn scan_chunks(data: &[u8]) -> Vec<(usize, usize)> {
+    let len = data.len();
+    let size = (len / (rayon::current_num_threads() * 4)).max(MIN_SCAN_CHUNK);
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < len {
+        let end = match data.get(start + size..) {
+            Some(rest) => rest
+                .iter()
+                .position(|b| *b == b'\n')
+                .map_or(len, |i| start + size + i + 1),
+            None => len,
+        };
+        chunks.push((start, end));
+        start = end;
+    }
+    chunks
+}
+
+/// Byte offset where each target line (1-based, sorted) starts. Lines past
+/// the end map to the end of the data.
+fn line_offsets(
+    data: &[u8],
+    chunks: &[(usize, usize)],
+    counts: &[usize],
+    targets: &[usize],
+) -> HashMap<usize, usize> {
+    // Line n starts after newline number n - 1. Hand each chunk the targets
+    // whose newline falls inside it.
+    let mut work: Vec<(usize, usize, Vec<usize>)> = Vec::new();
+    let mut seen = 0;
+    for (i, count) in counts.iter().enumerate() {
+        let wanted: Vec<usize> = targets
+            .iter()
+            .copied()
+            .filter(|t| *t > 1 && t - 1 > seen && t - 1 <= seen + count)
+            .collect();
+        if !wanted.is_empty() {
+            work.push((i, seen, wanted));
+        }
+        seen += count;
+    }
+
+    let mut offsets: HashMap<usize, usize> = work
+        .par_iter()
+        .flat_map_iter(|(i, before, wanted)| {
+            let (start, end) = chunks[*i];
+            let mut found = Vec::with_capacity(wanted.len());
+            let mut next = wanted.iter().peekable();
+            let positions = data[start..end]
+                .iter()
+                .enumerate()
+                .filter(|(_, b)| **b == b'\n')
+                .map(|(pos, _)| pos);
+            for (newline, pos) in (before + 1..).zip(positions) {
+                while let Some(target) = next.next_if(|t| **t - 1 == newline) {
+                    found.push((*target, start + pos + 1));
+                }
+                if next.peek().is_none() {
+                    break;
+                }
+            }
+            found
+        })
+        .collect();
+
+    for target in targets {
+        offsets
+            .entry(*target)
+            .or_insert(if *target <= 1 { 0 } else { data.len() });
+    }
+    offsets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SplitMode, SplitPlanner, SplitUnit, split_file_with_options};
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "file_spliter_parallel_{}_{}",
+            std::process::id(),
+            name
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Splits `input` both ways and returns the two sets of parts.
+    fn both_ways(dir: &Path, input: &Path, planner: SplitPlanner) -> [Vec<Vec<u8>>; 2] {
+        [false, true].map(|parallel| {
+            let out = dir.join(if parallel { "parallel" } else { "sequential" });
+            fs::create_dir_all(&out).unwrap();
+            let plan = planner.clone().with_output_dir(&out).build().unwrap();
+            let options = SplitOptions {
+                parallel,
+                ..plan.options
+            };
+            split_file_with_options(input, &plan.configs, options).unwrap();
+            plan.configs
+                .iter()
+                .map(|c| fs::read(&c.output_path).unwrap())
+                .collect()
+        })
+    }
+
+    #[test]
+    fn matches_the_sequential_path() {
+        let dir = scratch_dir("same");
+        let input = dir.join("data.csv");
+        let mut text = String::from("id,ข้อความ\n");
+        for i in 1..=500 {
+            text.push_str(&format!("{},แถวที่ {}\n", i, i));
+        }
+        text.push_str("501,no newline");
+        fs::write(&input, &text).unwrap();
+
+        let mode = SplitMode::Auto {
+            chunk_size: 120,
+            unit: SplitUnit::Lines,
+        };
+        let planner = SplitPlanner::new(&input, mode)
+            .with_header_lines(1)
+            .with_overlap(3);
+        let [sequential, parallel] = both_ways(&dir, &input, planner);
+        assert_eq!(sequential.len(), 5);
+        assert_eq!(sequential, parallel);
+        assert!(parallel[1].starts_with("id,ข้อความ\n118,".as_bytes()));
+        assert!(parallel[4].ends_with(b"501,no newline\n"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn manual_ranges_match_the_sequential_path() {
+        let dir = scratch_dir("manual");
+        let input = dir.join("data.txt");
+        let text: String = (1..=50).map(|i| format!("line {}\n", i)).collect();
+        fs::write(&input, &text).unwrap();
+
+        let mode = SplitMode::Manual {
+            ranges: vec![(40, 60), (1, 10), (5, 15), (50, 50)],
+            strict: false,
+        };
+        let [sequential, parallel] = both_ways(&dir, &input, SplitPlanner::new(&input, mode));
+        assert_eq!(sequential, parallel);
+        assert_eq!(parallel[3], b"line 50\n");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn offsets_across_chunks() {
+        let data: Vec<u8> = (1..=30)
+            .flat_map(|i| format!("{}\n", "x".repeat(i)).into_bytes())
+            .collect();
+        let targets = [1, 2, 7, 8, 29, 30, 31, 40];
+        let offsets_with = |chunks: &[(usize, usize)]| {
+            let counts: Vec<usize> = chunks
+                .iter()
+                .map(|&(s, e)| data[s..e].iter().filter(|b| **b == b'\n').count())
+                .collect();
+            line_offsets(&data, chunks, &counts, &targets)
+        };
+
+        let whole = offsets_with(&[(0, data.len())]);
+        let chunks: Vec<(usize, usize)> = (0..data.len())
+            .step_by(37)
+            .map(|s| (s, (s + 37).min(data.len())))
+            .collect();
+        assert_eq!(offsets_with(&chunks), whole);
+        assert_eq!(whole[&1], 0);
+        assert_eq!(whole[&2], 2);
+        assert_eq!(whole[&31], data.len());
+        assert_eq!(whole[&40], data.len());
+    }
+
+    #[test]
+    fn carriage_returns_use_the_sequential_path() {
+        let dir = scratch_dir("crlf");
+        let input = dir.join("data.txt");
+        fs::write(&input, "a\r\nb\r\nc\r\n").unwrap();
+        let parts = [SplitConfig::new(2, 3, dir.join("part.txt").display().to_string()).unwrap()];
+        let options = SplitOptions {
+            parallel: true,
+            ..SplitOptions::default()
+        };
+        assert_eq!(split_mapped(&input, &parts, &options), Ok(None));
+        split_file_with_options(&input, &parts, options).unwrap();
+        assert_eq!(fs::read(dir.join("part.txt")).unwrap(), b"b\nc\n");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn chunks_end_after_newlines() {
+        let data = "ภาษาไทย\n".repeat(MIN_SCAN_CHUNK / 10).into_bytes();
+        let chunks = scan_chunks(&data);
+        assert!(chunks.len() > 1);
+        assert_eq!(chunks.first().unwrap().0, 0);
+        assert_eq!(chunks.last().unwrap().1, data.len());
+        for pair in chunks.windows(2) {
+            assert_eq!(pair[0].1, pair[1].0);
+            assert_eq!(data[pair[0].1 - 1], b'\n');
+        }
+        assert_eq!(scan_chunks(b""), []);
+    }
+
+    #[test]
+    fn invalid_utf8_fails_like_the_sequential_path() {
+        let dir = scratch_dir("utf8");
+        let input = dir.join("data.txt");
+        fs::write(&input, b"ok\nbad \xff\nok\n").unwrap();
+        let part =
+            |name: &str| [SplitConfig::new(1, 3, dir.join(name).display().to_string()).unwrap()];
+
+        let options = SplitOptions {
+            parallel: true,
+            ..SplitOptions::default()
+        };
+        assert_eq!(split_mapped(&input, &part("a.txt"), &options), Ok(None));
+        let parallel = split_file_with_options(&input, &part("b.txt"), options);
+        let sequential = split_file_with_options(&input, &part("c.txt"), SplitOptions::default());
+        assert!(
+            parallel
+                .as_ref()
+                .unwrap_err()
+                .starts_with("Read error at line 2")
+        );
+        assert_eq!(parallel, sequential);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
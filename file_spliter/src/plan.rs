@@ -0,0 +1,1197 @@
+use crate::{
+    JsonChunkLimit, LineIndex, RecordTracker, SplitConfig, SplitOptions, SplitUnit, TextChunkSize,
+    scan_markdown_reader,
+};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+/// How a [`SplitPlanner`] decides where the parts start and end.
+///
+/// JSON and prose have no mode here: a plan is a list of line ranges fixed
+/// before anything is written, while [`split_json_array`](crate::split_json_array),
+/// [`split_json_lines`](crate::split_json_lines) and
+/// [`split_text`](crate::split_text) find each cut while streaming and only
+/// know the number of parts at the end. They take the part paths instead;
+/// build them with [`PartNames`] to name and place the parts the same way.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SplitMode {
+    /// One part per `(start, end)` line range, in the order given.
+    /// In strict mode overlapping ranges, gaps and ranges running past the
+    /// end of the file are errors instead of warnings.
+    Manual {
+        ranges: Vec<(usize, usize)>,
+        strict: bool,
+    },
+    /// Consecutive parts of `chunk_size` units; the last one takes the rest.
+    Auto { chunk_size: usize, unit: SplitUnit },
+    /// Consecutive parts sized by `shares` (weights such as `[70, 20, 10]`,
+    /// see [`parse_shares`]). With a seed, the lines are shuffled first.
+    Proportional {
+        shares: Vec<f64>,
+        unit: SplitUnit,
+        seed: Option<u64>,
+    },
+    /// Deals units out to `parts` parts in turn, so every part samples the
+    /// whole file.
+    RoundRobin { parts: usize, unit: SplitUnit },
+    /// One part per Markdown section, cut at headings of `level` or above.
+    Markdown { level: usize, repeat_parents: bool },
+}
+
+/// How the parts of a planned split are named.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum PartNaming {
+    /// `notes - Part 3.txt`. Markdown parts are named after their heading:
+    /// `notes - 03 Action Items.md`.
+    #[default]
+    Numbered,
+    /// A file name built from `{stem}` and `{ext}` of the input, the 1-based
+    /// part number `{n}` and `{title}` (the heading of a Markdown part,
+    /// `Part <n>` otherwise), e.g. `{stem}_{n}.{ext}`. Must contain `{n}`.
+    Template(String),
+}
+
+/// Result of planning: the parts to write plus anything the user should know
+/// about the plan before the files are created.
+#[derive(Debug)]
+pub struct SplitPlan {
+    pub configs: Vec<SplitConfig>,
+    pub warnings: Vec<String>,
+    /// For manual ranges: how much of the file the ranges cover.
+    pub coverage: Option<String>,
+    /// Options the plan needs (shuffle seed, record unit); pass them on to
+    /// [`split_file_with_options`](crate::split_file_with_options).
+    pub options: SplitOptions,
+}
+
+/// Turns a [`SplitMode`] and an input file into the [`SplitConfig`]s of a
+/// split, checking them against the file before anything is written.
+///
+/// ```no_run
+/// use file_spliter::{SplitMode, SplitPlanner, SplitUnit, split_file_with_options};
+///
+/// let mode = SplitMode::Auto { chunk_size: 200, unit: SplitUnit::Cues };
+/// let plan = SplitPlanner::new("talk.srt", mode)
+///     .with_output_dir("parts")
+///     .with_overlap(5)
+///     .build()?;
+/// split_file_with_options("talk.srt", &plan.configs, plan.options)?;
+/// # Ok::<(), String>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct SplitPlanner {
+    input_path: PathBuf,
+    mode: SplitMode,
+    output_dir: Option<PathBuf>,
+    naming: PartNaming,
+    header_lines: usize,
+    overlap: usize,
+}
+
+impl SplitPlanner {
+    /// Parts are written next to the input, numbered, with no header and no
+    /// overlap until set otherwise.
+    pub fn new(input_path: impl Into<PathBuf>, mode: SplitMode) -> Self {
+        SplitPlanner {
+            input_path: input_path.into(),
+            mode,
+            output_dir: None,
+            naming: PartNaming::default(),
+            header_lines: 0,
+            overlap: 0,
+        }
+    }
+
+    /// Writes the parts into `dir` instead of next to the input.
+    pub fn with_output_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.output_dir = Some(dir.into());
+        self
+    }
+
+    pub fn with_naming(mut self, naming: PartNaming) -> Self {
+        self.naming = naming;
+        self
+    }
+
+    /// Repeats the first `lines` lines (e.g. a CSV header) at the top of
+    /// every part. Not available for Markdown splits.
+    pub fn with_header_lines(mut self, lines: usize) -> Self {
+        self.header_lines = lines;
+        self
+    }
+
+    /// Starts every part but the first `units` units (lines, cues or CSV
+    /// records) early, repeating the end of the previous part for context.
+    /// Only for [`SplitMode::Auto`] and [`SplitMode::Proportional`].
+    pub fn with_overlap(mut self, units: usize) -> Self {
+        self.overlap = units;
+        self
+    }
+
+    pub fn input_path(&self) -> &Path {
+        &self.input_path
+    }
+
+    pub fn build(&self) -> Result<SplitPlan, String> {
+        validate_input_path(&self.input_path)?;
+        self.build_input(PlanInput::File(&self.input_path))
+    }
+
+    /// Plans against `input`; the input path only names the parts. Shared by
+    /// [`build`](Self::build) and
+    /// [`build_from_bytes`](Self::build_from_bytes).
+    pub(crate) fn build_input(&self, input: PlanInput) -> Result<SplitPlan, String> {
+        // 1. Validate & Prep (Common for all modes)
+        let contiguous = matches!(
+            self.mode,
+            SplitMode::Auto { .. } | SplitMode::Proportional { .. }
+        );
+        if self.overlap > 0 && !contiguous {
+            return Err("Overlap only applies to chunk and share splits.".to_string());
+        }
+        if matches!(self.mode, SplitMode::Markdown { .. }) && self.header_lines > 0 {
+            return Err(
+                "Markdown splits repeat parent headings instead of header lines.".to_string(),
+            );
+        }
+        let header = read_header_lines(input, self.header_lines)?;
+        let names = PartNames::new(
+            &self.input_path,
+            self.output_dir.as_deref(),
+            self.naming.clone(),
+        )?;
+
+        // 2. Delegate to the specific function
+        match &self.mode {
+            SplitMode::Manual { ranges, strict } => {
+                plan_manual_split(input, ranges, *strict, &header, &names)
+            }
+            SplitMode::Auto { chunk_size, unit } => {
+                let configs =
+                    plan_auto_split(input, *chunk_size, *unit, &header, self.overlap, &names)?;
+                Ok(SplitPlan {
+                    configs,
+                    warnings: Vec::new(),
+                    coverage: None,
+                    options: SplitOptions::default(),
+                })
+            }
+            SplitMode::Proportional { shares, unit, seed } => {
+                plan_proportional_split(input, shares, *unit, &header, *seed, self.overlap, &names)
+            }
+            SplitMode::RoundRobin { parts, unit } => {
+                plan_round_robin_split(input, *parts, *unit, &header, &names)
+            }
+            SplitMode::Markdown {
+                level,
+                repeat_parents,
+            } => {
+                let configs = plan_markdown_split(input, *level, *repeat_parents, &names)?;
+                Ok(SplitPlan {
+                    configs,
+                    warnings: Vec::new(),
+                    coverage: None,
+                    options: SplitOptions::default(),
+                })
+            }
+        }
+    }
+
+    /// Async version of [`build`](Self::build). Planning reads the whole
+    /// input (to count lines, cues or sections), so it runs on tokio's
+    /// blocking pool.
+    #[cfg(feature = "async")]
+    pub async fn build_async(self) -> Result<SplitPlan, String> {
+        tokio::task::spawn_blocking(move || self.build())
+            .await
+            .map_err(|e| format!("Planning task failed: {}", e))?
+    }
+}
+
+// =========================================================================
+// HELPER 1: PARSING
+// For front-ends that take ranges, shares and chunk sizes as text.
+// =========================================================================
+
+/// Parses a line range such as `"100-200"`.
+pub fn parse_range(range: &str) -> Result<(usize, usize), String> {
+    let parts: Vec<&str> = range.split('-').collect();
+    if parts.len() != 2 {
+        return Err(format!("Invalid range '{}'", range));
+    }
+    let start = parts[0].trim().parse::<usize>().map_err(|_| "Bad start")?;
+    let end = parts[1].trim().parse::<usize>().map_err(|_| "Bad end")?;
+    Ok((start, end))
+}
+
+/// Parses "70,20,10" (weights) or "50%,50%" (percentages) into fractions.
+pub fn parse_shares(shares: &str) -> Result<Vec<f64>, String> {
+    let items: Vec<&str> = shares
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect();
+    if items.is_empty() {
+        return Err(format!("No shares given in '{}'", shares));
+    }
+
+    let percent_count = items.iter().filter(|s| s.ends_with('%')).count();
+    if percent_count != 0 && percent_count != items.len() {
+        return Err(format!(
+            "Mixing percentages and weights in '{}' is not supported",
+            shares
+        ));
+    }
+
+    let mut values = Vec::new();
+    for item in &items {
+        let value = item
+            .trim_end_matches('%')
+            .parse::<f64>()
+            .map_err(|_| format!("Invalid share '{}'", item))?;
+        values.push(value);
+    }
+
+    let sum: f64 = values.iter().sum();
+    if percent_count != 0 && (sum - 100.0).abs() > 0.01 {
+        return Err(format!("Percentages must add up to 100% (got {}%)", sum));
+    }
+
+    normalize_shares(&values)
+}
+
+/// Parses a JSON chunk limit: a plain number is a record count, a number
+/// with a `B`, `KB`, `MB` or `GB` suffix is a byte budget.
+pub fn parse_json_limit(limit: &str) -> Result<JsonChunkLimit, String> {
+    let upper = limit.trim().to_ascii_uppercase();
+    let (digits, multiplier) = if let Some(n) = upper.strip_suffix("GB") {
+        (n, 1024 * 1024 * 1024)
+    } else if let Some(n) = upper.strip_suffix("MB") {
+        (n, 1024 * 1024)
+    } else if let Some(n) = upper.strip_suffix("KB") {
+        (n, 1024)
+    } else if let Some(n) = upper.strip_suffix('B') {
+        (n, 1)
+    } else {
+        let count = upper
+            .parse::<usize>()
+            .map_err(|_| format!("Invalid record count '{}'", limit))?;
+        return Ok(JsonChunkLimit::Records(count));
+    };
+
+    let value = digits
+        .trim()
+        .parse::<u64>()
+        .map_err(|_| format!("Invalid size '{}'", limit))?;
+    Ok(JsonChunkLimit::Bytes(value * multiplier))
+}
+
+/// Parses a text chunk size: a line count (`200`), characters (`4000c`,
+/// `4000chars`) or estimated tokens (`1000t`, `1000tokens`).
+pub fn parse_text_size(size: &str) -> Result<TextChunkSize, String> {
+    let lower = size.trim().to_ascii_lowercase();
+    let (digits, make): (&str, fn(usize) -> TextChunkSize) = if let Some(n) = lower
+        .strip_suffix("chars")
+        .or_else(|| lower.strip_suffix('c'))
+    {
+        (n, TextChunkSize::Chars)
+    } else if let Some(n) = lower
+        .strip_suffix("tokens")
+        .or_else(|| lower.strip_suffix('t'))
+    {
+        (n, TextChunkSize::Tokens)
+    } else {
+        (lower.as_str(), TextChunkSize::Lines)
+    };
+
+    let value = digits
+        .trim()
+        .parse::<usize>()
+        .map_err(|_| format!("Invalid text size '{}'", size))?;
+    if value == 0 {
+        return Err("Text size must be at least 1".to_string());
+    }
+    Ok(make(value))
+}
+
+/// Scales positive weights so they add up to 1.
+fn normalize_shares(shares: &[f64]) -> Result<Vec<f64>, String> {
+    if shares.is_empty() {
+        return Err("No shares given.".to_string());
+    }
+    if let Some(bad) = shares.iter().find(|v| !v.is_finite() || **v <= 0.0) {
+        return Err(format!("Share '{}' must be a positive number", bad));
+    }
+    let sum: f64 = shares.iter().sum();
+    Ok(shares.iter().map(|v| v / sum).collect())
+}
+
+// =========================================================================
+// HELPER 2: PART NAMES
+// =========================================================================
+
+/// Where the parts of an input file are written and how they are named.
+#[derive(Debug, Clone)]
+pub struct PartNames {
+    parent: PathBuf,
+    stem: String,
+    ext: String,
+    naming: PartNaming,
+}
+
+impl PartNames {
+    /// Names parts after the input file, in `output_dir` if given or next to
+    /// the input otherwise.
+    pub fn new(
+        input_path: &Path,
+        output_dir: Option<&Path>,
+        naming: PartNaming,
+    ) -> Result<Self, String> {
+        if let PartNaming::Template(template) = &naming
+            && !template.contains("{n}")
+        {
+            return Err(format!(
+                "Naming template '{}' must contain {{n}} so parts get different names.",
+                template
+            ));
+        }
+
+        // Use provided output_dir or default to input file's parent
+        let parent = match output_dir {
+            Some(dir) => dir.to_path_buf(),
+            None => input_path
+                .parent()
+                .unwrap_or_else(|| Path::new("."))
+                .to_path_buf(),
+        };
+
+        let stem = input_path
+            .file_stem()
+            .ok_or("Invalid filename")?
+            .to_string_lossy()
+            .to_string();
+        let ext = input_path
+            .extension()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+
+        Ok(PartNames {
+            parent,
+            stem,
+            ext,
+            naming,
+        })
+    }
+
+    /// Path of part `index` (0-based).
+    pub fn part(&self, index: usize) -> String {
+        match &self.naming {
+            PartNaming::Numbered => {
+                let new_name = format!("{} - Part {}{}", self.stem, index + 1, self.dot_ext());
+                self.parent.join(new_name).to_string_lossy().to_string()
+            }
+            PartNaming::Template(template) => {
+                self.templated(template, index, &format!("Part {}", index + 1))
+            }
+        }
+    }
+
+    /// Path of part `index` named after `title`: "notes - 03 Action
+    /// Items.md". Falls back to the numbered name when the title has
+    /// nothing usable in a file name.
+    pub fn titled_part(&self, index: usize, title: &str) -> String {
+        let cleaned: String = title
+            .chars()
+            .filter(|c| !matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|'))
+            .filter(|c| !c.is_control())
+            .collect();
+        let cleaned: String = cleaned
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .chars()
+            .take(60)
+            .collect();
+        let cleaned = cleaned.trim_end_matches(['.', ' ']);
+        if cleaned.is_empty() {
+            return self.part(index);
+        }
+
+        match &self.naming {
+            PartNaming::Numbered => {
+                let new_name = format!(
+                    "{} - {:02} {}{}",
+                    self.stem,
+                    index + 1,
+                    cleaned,
+                    self.dot_ext()
+                );
+                self.parent.join(new_name).to_string_lossy().to_string()
+            }
+            PartNaming::Template(template) => self.templated(template, index, cleaned),
+        }
+    }
+
+    fn dot_ext(&self) -> String {
+        if self.ext.is_empty() {
+            String::new()
+        } else {
+            format!(".{}", self.ext)
+        }
+    }
+
+    fn templated(&self, template: &str, index: usize, title: &str) -> String {
+        let new_name = template
+            .replace("{stem}", &self.stem)
+            .replace("{ext}", &self.ext)
+            .replace("{n}", &(index + 1).to_string())
+            .replace("{title}", title);
+        self.parent.join(new_name).to_string_lossy().to_string()
+    }
+}
+
+// =========================================================================
+// HELPER 3: READING THE INPUT
+// =========================================================================
+
+/// What a plan is built from: a file, or input already in memory. Planning
+/// reads the input more than once, so every pass gets a fresh reader.
+#[derive(Clone, Copy)]
+pub(crate) enum PlanInput<'a> {
+    File(&'a Path),
+    Bytes(&'a [u8]),
+}
+
+impl<'a> PlanInput<'a> {
+    fn reader(self) -> Result<Box<dyn BufRead + 'a>, String> {
+        match self {
+            PlanInput::File(path) => {
+                let file = File::open(path).map_err(|e| e.to_string())?;
+                Ok(Box::new(BufReader::new(file)))
+            }
+            PlanInput::Bytes(bytes) => Ok(Box::new(bytes)),
+        }
+    }
+}
+
+/// Checks that `path` names an existing file, so every front-end reports a
+/// missing input the same way.
+pub fn validate_input_path(path: &Path) -> Result<(), String> {
+    if !path.exists() {
+        return Err(format!("Input path does not exist: '{}'", path.display()));
+    }
+    if !path.is_file() {
+        return Err(format!("Input path is not a file: '{}'", path.display()));
+    }
+    Ok(())
+}
+
+fn count_total_lines(input: PlanInput) -> Result<usize, String> {
+    // An up-to-date line index already knows the answer
+    if let PlanInput::File(path) = input
+        && let Ok(Some(index)) = LineIndex::load(path)
+    {
+        return Ok(index.total_lines());
+    }
+    Ok(input.reader()?.lines().count())
+}
+
+/// Reads the first `count` lines, to be repeated at the top of every part.
+fn read_header_lines(input: PlanInput, count: usize) -> Result<Vec<String>, String> {
+    if count == 0 {
+        return Ok(Vec::new());
+    }
+    let header: Vec<String> = input
+        .reader()?
+        .lines()
+        .take(count)
+        .collect::<Result<_, _>>()
+        .map_err(|e| format!("Cannot read header: {}", e))?;
+    if header.len() < count {
+        return Err(format!(
+            "File has only {} lines, fewer than the {} header lines requested.",
+            header.len(),
+            count
+        ));
+    }
+    Ok(header)
+}
+
+/// Splits `total` units by `fractions` using the largest-remainder method,
+/// so the sizes always add up to exactly `total`.
+fn allocate_by_fractions(total: usize, fractions: &[f64]) -> Vec<usize> {
+    let exact: Vec<f64> = fractions.iter().map(|f| f * total as f64).collect();
+    let mut sizes: Vec<usize> = exact.iter().map(|e| e.floor() as usize).collect();
+
+    let mut by_remainder: Vec<usize> = (0..sizes.len()).collect();
+    by_remainder.sort_by(|&a, &b| {
+        let ra = exact[a] - sizes[a] as f64;
+        let rb = exact[b] - sizes[b] as f64;
+        rb.total_cmp(&ra)
+    });
+
+    let assigned: usize = sizes.iter().sum();
+    for &i in by_remainder.iter().take(total.saturating_sub(assigned)) {
+        sizes[i] += 1;
+    }
+    sizes
+}
+
+/// Where each unit of the input lives, so unit counts can be turned back
+/// into line ranges.
+enum UnitMap {
+    /// Every line after the header is a unit.
+    Lines { header_lines: usize, count: usize },
+    /// Line span of every unit. Spans are contiguous and cover the file
+    /// after the header.
+    Spans(Vec<(usize, usize)>),
+}
+
+impl UnitMap {
+    /// Maps the units of the file, ignoring the first `header_lines` lines.
+    fn scan(input: PlanInput, unit: SplitUnit, header_lines: usize) -> Result<Self, String> {
+        match unit {
+            SplitUnit::Lines => Ok(UnitMap::Lines {
+                header_lines,
+                count: count_total_lines(input)?.saturating_sub(header_lines),
+            }),
+            SplitUnit::Cues | SplitUnit::CsvRecords => {
+                scan_unit_spans(input.reader()?, unit, header_lines).map(UnitMap::Spans)
+            }
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            UnitMap::Lines { count, .. } => *count,
+            UnitMap::Spans(spans) => spans.len(),
+        }
+    }
+
+    /// Line range covering units `first..=last` (0-based).
+    fn line_range(&self, first: usize, last: usize) -> (usize, usize) {
+        match self {
+            UnitMap::Lines { header_lines, .. } => {
+                (header_lines + first + 1, header_lines + last + 1)
+            }
+            UnitMap::Spans(spans) => (spans[first].0, spans[last].1),
+        }
+    }
+}
+
+/// Finds where each cue or CSV record starts (see [`RecordTracker`]).
+fn scan_unit_spans<R: BufRead>(
+    reader: R,
+    unit: SplitUnit,
+    header_lines: usize,
+) -> Result<Vec<(usize, usize)>, String> {
+    let mut starts = Vec::new();
+    let mut records = RecordTracker::new(unit);
+    let mut total_lines = 0;
+    for (index, line_result) in reader.lines().enumerate() {
+        let line = line_result.map_err(|e| format!("Read error at line {}: {}", index + 1, e))?;
+        let current_line = index + 1;
+        total_lines = current_line;
+        if current_line <= header_lines {
+            continue;
+        }
+
+        if records.starts_record(&line) {
+            starts.push(current_line);
+        }
+    }
+
+    if records.in_quotes() {
+        return Err("CSV ends inside a quoted field (unbalanced quotes).".to_string());
+    }
+
+    let mut spans = Vec::new();
+    for (i, &start) in starts.iter().enumerate() {
+        let start = if i == 0 { header_lines + 1 } else { start };
+        let end = starts.get(i + 1).map_or(total_lines, |next| next - 1);
+        spans.push((start, end));
+    }
+    Ok(spans)
+}
+
+// =========================================================================
+// HELPER 4: RANGE VALIDATION
+// Runs at plan time, so bad ranges are reported before any file is created.
+// =========================================================================
+
+/// Outcome of checking manual ranges against the input file.
+struct RangeCheck {
+    /// Problems that make the plan impossible (always fatal).
+    errors: Vec<String>,
+    /// Suspicious but workable ranges (fatal only in strict mode).
+    warnings: Vec<String>,
+    coverage: String,
+}
+
+/// Lines up to `header_lines` are repeated in every part, so they never
+/// count as a gap.
+fn check_ranges(bounds: &[(usize, usize)], header_lines: usize, total_lines: usize) -> RangeCheck {
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
+
+    // 1. Ranges against EOF and against the previous range
+    for (i, &(start, end)) in bounds.iter().enumerate() {
+        if start > total_lines {
+            errors.push(format!(
+                "Range {}-{} (Part {}) starts past the end of the file ({} lines).",
+                start,
+                end,
+                i + 1,
+                total_lines
+            ));
+        } else if end > total_lines {
+            warnings.push(format!(
+                "Range {}-{} (Part {}) extends past the end of the file and will stop at line {}.",
+                start,
+                end,
+                i + 1,
+                total_lines
+            ));
+        }
+
+        if i > 0 && start < bounds[i - 1].0 {
+            warnings.push(format!(
+                "Range {}-{} (Part {}) starts before the previous range {}-{}.",
+                start,
+                end,
+                i + 1,
+                bounds[i - 1].0,
+                bounds[i - 1].1
+            ));
+        }
+    }
+
+    // 2. Overlaps: walk the ranges in start order, remembering the range
+    //    that reaches furthest so far
+    let mut order: Vec<usize> = (0..bounds.len()).collect();
+    order.sort_by_key(|&i| bounds[i]);
+
+    let mut reach: Option<usize> = None;
+    for &i in &order {
+        let (start, end) = bounds[i];
+        if let Some(r) = reach {
+            let (r_start, r_end) = bounds[r];
+            if start <= r_end {
+                warnings.push(format!(
+                    "Range {}-{} (Part {}) overlaps range {}-{} (Part {}) on lines {}-{}.",
+                    start,
+                    end,
+                    i + 1,
+                    r_start,
+                    r_end,
+                    r + 1,
+                    start,
+                    end.min(r_end)
+                ));
+            }
+            if end <= r_end {
+                continue;
+            }
+        }
+        reach = Some(i);
+    }
+
+    // 3. Gaps & coverage, counting only lines that actually exist
+    let mut gaps = Vec::new();
+    let mut covered = 0;
+    let mut next_line = header_lines + 1;
+    for &i in &order {
+        let (start, end) = bounds[i];
+        let end = end.min(total_lines);
+        if start > end || end < next_line {
+            continue;
+        }
+        if start > next_line {
+            gaps.push(format!("{}-{}", next_line, start - 1));
+        }
+        covered += end - start.max(next_line) + 1;
+        next_line = end + 1;
+    }
+    if next_line <= total_lines {
+        gaps.push(format!("{}-{}", next_line, total_lines));
+    }
+    if !gaps.is_empty() {
+        warnings.push(format!(
+            "Lines not covered by any range: {}",
+            gaps.join(", ")
+        ));
+    }
+
+    let data_lines = total_lines.saturating_sub(header_lines);
+    let percent = if data_lines == 0 {
+        0.0
+    } else {
+        covered as f64 * 100.0 / data_lines as f64
+    };
+    let coverage = format!(
+        "Ranges cover {} of {} lines ({:.1}%).",
+        covered, data_lines, percent
+    );
+
+    RangeCheck {
+        errors,
+        warnings,
+        coverage,
+    }
+}
+
+// =========================================================================
+// HELPER 5: SPECIFIC LOGIC HANDLERS
+// These do the actual heavy thinking for each mode.
+// =========================================================================
+
+/// Logic for: "1-100", "200-300"
+fn plan_manual_split(
+    input: PlanInput,
+    bounds: &[(usize, usize)],
+    strict: bool,
+    header: &[String],
+    names: &PartNames,
+) -> Result<SplitPlan, String> {
+    // 1. Check the ranges against the real file before anything is written
+    if bounds.is_empty() {
+        return Err("No ranges given.".to_string());
+    }
+    let total_lines = count_total_lines(input)?;
+    let check = check_ranges(bounds, header.len(), total_lines);
+    if !check.errors.is_empty() {
+        return Err(check.errors.join("\n"));
+    }
+    if strict && !check.warnings.is_empty() {
+        return Err(format!(
+            "Strict mode rejected the ranges:\n{}",
+            check.warnings.join("\n")
+        ));
+    }
+
+    // 2. Generate names & save; parts that start inside the header already
+    //    contain it
+    let mut configs = Vec::new();
+    for (i, &(start, end)) in bounds.iter().enumerate() {
+        let output = names.part(i);
+        let mut config = SplitConfig::new(start, end, output)?;
+        if start > header.len() {
+            config = config.with_preamble(header.to_vec());
+        }
+        configs.push(config);
+    }
+
+    Ok(SplitPlan {
+        configs,
+        warnings: check.warnings,
+        coverage: Some(check.coverage),
+        options: SplitOptions::default(),
+    })
+}
+
+/// Turns consecutive part sizes (counted in units) into line ranges, each
+/// part but the first reaching `overlap` units back into the one before.
+/// Parts that end up with no units are skipped with a warning.
+fn configs_from_sizes(
+    sizes: &[usize],
+    units: &UnitMap,
+    header: &[String],
+    overlap: usize,
+    names: &PartNames,
+) -> Result<(Vec<SplitConfig>, Vec<String>), String> {
+    let mut configs = Vec::new();
+    let mut warnings = Vec::new();
+    let mut next_unit: usize = 0;
+
+    for (index, &size) in sizes.iter().enumerate() {
+        if size == 0 {
+            warnings.push(format!(
+                "Part {} would be empty and was skipped.",
+                index + 1
+            ));
+            continue;
+        }
+
+        let first = next_unit.saturating_sub(overlap);
+        let (start, end) = units.line_range(first, next_unit + size - 1);
+        let output = names.part(index);
+        configs.push(SplitConfig::new(start, end, output)?.with_preamble(header.to_vec()));
+
+        next_unit += size;
+    }
+    Ok((configs, warnings))
+}
+
+/// Logic for: "-n 3000"
+fn plan_auto_split(
+    input: PlanInput,
+    chunk_size: usize,
+    unit: SplitUnit,
+    header: &[String],
+    overlap: usize,
+    names: &PartNames,
+) -> Result<Vec<SplitConfig>, String> {
+    if chunk_size == 0 {
+        return Err("Chunk size must be at least 1.".to_string());
+    }
+    if overlap >= chunk_size {
+        return Err(format!(
+            "Overlap ({}) must be smaller than the chunk size ({}).",
+            overlap, chunk_size
+        ));
+    }
+
+    // 1. Count lines (or records) first
+    let units = UnitMap::scan(input, unit, header.len())?;
+    let total = units.len();
+    if total == 0 {
+        return Err("File is empty.".to_string());
+    }
+
+    // 2. Full chunks, then whatever is left for the last part
+    let mut sizes = vec![chunk_size; total / chunk_size];
+    if total % chunk_size != 0 {
+        sizes.push(total % chunk_size);
+    }
+
+    let (configs, _) = configs_from_sizes(&sizes, &units, header, overlap, names)?;
+    Ok(configs)
+}
+
+/// Logic for: "70,20,10" or "50%,50%"
+fn plan_proportional_split(
+    input: PlanInput,
+    shares: &[f64],
+    unit: SplitUnit,
+    header: &[String],
+    seed: Option<u64>,
+    overlap: usize,
+    names: &PartNames,
+) -> Result<SplitPlan, String> {
+    // 1. Check the shares & that the combination makes sense
+    let fractions = normalize_shares(shares)?;
+    if seed.is_some() && unit != SplitUnit::Lines {
+        return Err("Shuffling is only supported when splitting by lines.".to_string());
+    }
+
+    // 2. Count units
+    let units = UnitMap::scan(input, unit, header.len())?;
+    if units.len() == 0 {
+        return Err("File is empty.".to_string());
+    }
+
+    // 3. Size each part, then map it back to lines
+    let sizes = allocate_by_fractions(units.len(), &fractions);
+    let (configs, warnings) = configs_from_sizes(&sizes, &units, header, overlap, names)?;
+
+    Ok(SplitPlan {
+        configs,
+        warnings,
+        coverage: None,
+        options: SplitOptions {
+            shuffle_seed: seed,
+            ..SplitOptions::default()
+        },
+    })
+}
+
+/// Logic for: deal lines (or records) out to N parts in turn
+fn plan_round_robin_split(
+    input: PlanInput,
+    parts: usize,
+    unit: SplitUnit,
+    header: &[String],
+    names: &PartNames,
+) -> Result<SplitPlan, String> {
+    if parts == 0 {
+        return Err("Number of parts must be at least 1.".to_string());
+    }
+
+    // 1. Count units; every part shares the same body range
+    let units = UnitMap::scan(input, unit, header.len())?;
+    if units.len() == 0 {
+        return Err("File is empty.".to_string());
+    }
+    let (start, end) = units.line_range(0, units.len() - 1);
+
+    // 2. More parts than units would leave some parts empty
+    let mut warnings = Vec::new();
+    let used = parts.min(units.len());
+    if used < parts {
+        warnings.push(format!(
+            "Only {} units to deal; writing {} parts instead of {}.",
+            units.len(),
+            used,
+            parts
+        ));
+    }
+
+    let mut configs = Vec::new();
+    for turn in 0..used {
+        let config = SplitConfig::new(start, end, names.part(turn))?
+            .with_preamble(header.to_vec())
+            .with_interleave(used, turn)?;
+        configs.push(config);
+    }
+
+    Ok(SplitPlan {
+        configs,
+        warnings,
+        coverage: None,
+        options: SplitOptions {
+            record_unit: unit,
+            ..SplitOptions::default()
+        },
+    })
+}
+
+/// Logic for: split at "## Heading" lines
+fn plan_markdown_split(
+    input: PlanInput,
+    level: usize,
+    repeat_parents: bool,
+    names: &PartNames,
+) -> Result<Vec<SplitConfig>, String> {
+    let sections = scan_markdown_reader(input.reader()?, level)?;
+
+    let mut configs = Vec::new();
+    for (index, section) in sections.into_iter().enumerate() {
+        let title = section.title.as_deref().unwrap_or("Preface");
+        let output = names.titled_part(index, title);
+        let mut config = SplitConfig::new(section.start, section.end, output)?;
+        if repeat_parents && !section.parents.is_empty() {
+            let mut preamble = section.parents;
+            preamble.push(String::new());
+            config = config.with_preamble(preamble);
+        }
+        configs.push(config);
+    }
+    Ok(configs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(count: usize) -> String {
+        (1..=count).map(|i| format!("line {}\n", i)).collect()
+    }
+
+    fn ranges(input: &str, ranges: &[(usize, usize)], strict: bool) -> Result<SplitPlan, String> {
+        let mode = SplitMode::Manual {
+            ranges: ranges.to_vec(),
+            strict,
+        };
+        SplitPlanner::new("notes.txt", mode).build_from_bytes(input.as_bytes())
+    }
+
+    #[test]
+    fn clean_ranges_cover_the_file() {
+        let check = check_ranges(&[(1, 4), (5, 10)], 0, 10);
+        assert!(check.errors.is_empty());
+        assert!(check.warnings.is_empty());
+        assert_eq!(check.coverage, "Ranges cover 10 of 10 lines (100.0%).");
+    }
+
+    #[test]
+    fn range_past_eof() {
+        let check = check_ranges(&[(1, 5), (6, 12), (13, 20)], 0, 10);
+        assert_eq!(
+            check.errors,
+            ["Range 13-20 (Part 3) starts past the end of the file (10 lines)."]
+        );
+        assert_eq!(
+            check.warnings,
+            ["Range 6-12 (Part 2) extends past the end of the file and will stop at line 10."]
+        );
+        assert_eq!(check.coverage, "Ranges cover 10 of 10 lines (100.0%).");
+    }
+
+    #[test]
+    fn overlaps_order_and_gaps() {
+        let check = check_ranges(&[(1, 6), (8, 9), (4, 7)], 0, 12);
+        assert!(check.errors.is_empty());
+        assert_eq!(
+            check.warnings,
+            [
+                "Range 4-7 (Part 3) starts before the previous range 8-9.",
+                "Range 4-7 (Part 3) overlaps range 1-6 (Part 1) on lines 4-6.",
+                "Lines not covered by any range: 10-12",
+            ]
+        );
+        assert_eq!(check.coverage, "Ranges cover 9 of 12 lines (75.0%).");
+    }
+
+    #[test]
+    fn overlap_with_an_earlier_longer_range() {
+        // 3-4 ends inside 1-10, so 8-12 still overlaps 1-10
+        let check = check_ranges(&[(1, 10), (3, 4), (8, 12)], 0, 12);
+        assert_eq!(
+            check.warnings,
+            [
+                "Range 3-4 (Part 2) overlaps range 1-10 (Part 1) on lines 3-4.",
+                "Range 8-12 (Part 3) overlaps range 1-10 (Part 1) on lines 8-10.",
+            ]
+        );
+    }
+
+    #[test]
+    fn header_is_not_a_gap() {
+        let check = check_ranges(&[(3, 5), (8, 10)], 2, 10);
+        assert_eq!(check.warnings, ["Lines not covered by any range: 6-7"]);
+        assert_eq!(check.coverage, "Ranges cover 6 of 8 lines (75.0%).");
+    }
+
+    #[test]
+    fn strict_mode_rejects_warnings() {
+        let input = lines(10);
+        let plan = ranges(&input, &[(1, 4), (6, 12)], false).unwrap();
+        assert_eq!(plan.configs.len(), 2);
+        assert_eq!(plan.warnings.len(), 2);
+        assert_eq!(
+            plan.coverage.as_deref(),
+            Some("Ranges cover 9 of 10 lines (90.0%).")
+        );
+
+        let error = ranges(&input, &[(1, 4), (6, 12)], true).unwrap_err();
+        assert!(error.starts_with("Strict mode rejected the ranges:\n"));
+        assert!(error.contains("Lines not covered by any range: 5-5"));
+        assert!(ranges(&input, &[(1, 4), (5, 10)], true).is_ok());
+    }
+
+    #[test]
+    fn errors_are_fatal_in_both_modes() {
+        let input = lines(3);
+        for strict in [false, true] {
+            assert_eq!(
+                ranges(&input, &[(1, 2), (5, 6)], strict).unwrap_err(),
+                "Range 5-6 (Part 2) starts past the end of the file (3 lines)."
+            );
+        }
+        assert_eq!(ranges(&input, &[], false).unwrap_err(), "No ranges given.");
+    }
+
+    #[test]
+    fn largest_remainder_rounding() {
+        assert_eq!(allocate_by_fractions(10, &[0.7, 0.2, 0.1]), [7, 2, 1]);
+        // 3.33.. each: the one spare unit goes to the first largest remainder
+        assert_eq!(allocate_by_fractions(10, &[1.0 / 3.0; 3]), [4, 3, 3]);
+        // 1.25, 1.25, 2.5: the halves win over the quarters
+        assert_eq!(allocate_by_fractions(5, &[0.25, 0.25, 0.5]), [1, 1, 3]);
+        assert_eq!(
+            allocate_by_fractions(2, &[0.2, 0.2, 0.2, 0.2, 0.2]),
+            [1, 1, 0, 0, 0]
+        );
+        for total in 0..50 {
+            let sizes = allocate_by_fractions(total, &[0.45, 0.35, 0.2]);
+            assert_eq!(sizes.iter().sum::<usize>(), total);
+        }
+    }
+
+    #[test]
+    fn seeded_proportional_split_is_deterministic() {
+        let input = lines(100);
+        let split = |seed| {
+            let mode = SplitMode::Proportional {
+                shares: vec![70.0, 30.0],
+                unit: SplitUnit::Lines,
+                seed: Some(seed),
+            };
+            let plan = SplitPlanner::new("data.txt", mode)
+                .build_from_bytes(input.as_bytes())
+                .unwrap();
+            crate::split_str(&input, &plan.configs, plan.options).unwrap()
+        };
+
+        let parts = split(42);
+        assert_eq!(parts, split(42));
+        assert_ne!(parts, split(7));
+        assert_eq!(parts[0].lines().count(), 70);
+        assert_eq!(parts[1].lines().count(), 30);
+
+        // Every line once, in a shuffled order
+        let mut all: Vec<&str> = parts.iter().flat_map(|p| p.lines()).collect();
+        assert_ne!(all, input.lines().collect::<Vec<_>>());
+        all.sort();
+        let mut expected: Vec<&str> = input.lines().collect();
+        expected.sort();
+        assert_eq!(all, expected);
+    }
+
+    #[test]
+    fn empty_shares_are_skipped() {
+        let mode = SplitMode::Proportional {
+            shares: vec![90.0, 9.0, 1.0],
+            unit: SplitUnit::Lines,
+            seed: None,
+        };
+        let plan = SplitPlanner::new("data.txt", mode)
+            .build_from_bytes(lines(10).as_bytes())
+            .unwrap();
+        let ranges: Vec<(usize, usize)> = plan.configs.iter().map(|c| (c.start, c.end)).collect();
+        assert_eq!(ranges, [(1, 9), (10, 10)]);
+        assert_eq!(plan.warnings, ["Part 3 would be empty and was skipped."]);
+    }
+
+    fn deal(input: &str, parts: usize, unit: SplitUnit, header_lines: usize) -> Vec<String> {
+        let plan = SplitPlanner::new("data.txt", SplitMode::RoundRobin { parts, unit })
+            .with_header_lines(header_lines)
+            .build_from_bytes(input.as_bytes())
+            .unwrap();
+        crate::split_str(input, &plan.configs, plan.options).unwrap()
+    }
+
+    #[test]
+    fn round_robin_deals_lines_in_turn() {
+        assert_eq!(
+            deal(&lines(7), 3, SplitUnit::Lines, 0),
+            [
+                "line 1\nline 4\nline 7\n",
+                "line 2\nline 5\n",
+                "line 3\nline 6\n"
+            ]
+        );
+    }
+
+    #[test]
+    fn round_robin_keeps_records_whole() {
+        let input = "id,text\n1,\"a\nb\"\n2,c\n3,\"d\ne\"\n";
+        assert_eq!(
+            deal(input, 2, SplitUnit::CsvRecords, 1),
+            ["id,text\n1,\"a\nb\"\n3,\"d\ne\"\n", "id,text\n2,c\n"]
+        );
+
+        let cues = "1\nA\n\n2\nB\n\n3\nC\n";
+        assert_eq!(
+            deal(cues, 2, SplitUnit::Cues, 0),
+            ["1\nA\n\n3\nC\n", "2\nB\n\n"]
+        );
+    }
+
+    #[test]
+    fn round_robin_with_more_parts_than_units() {
+        let mode = SplitMode::RoundRobin {
+            parts: 5,
+            unit: SplitUnit::Lines,
+        };
+        let plan = SplitPlanner::new("data.txt", mode)
+            .build_from_bytes(lines(3).as_bytes())
+            .unwrap();
+        assert_eq!(plan.configs.len(), 3);
+        assert_eq!(
+            plan.warnings,
+            ["Only 3 units to deal; writing 3 parts instead of 5."]
+        );
+
+        let none = SplitMode::RoundRobin {
+            parts: 0,
+            unit: SplitUnit::Lines,
+        };
+        let error = SplitPlanner::new("data.txt", none)
+            .build_from_bytes(b"x\n")
+            .unwrap_err();
+        assert_eq!(error, "Number of parts must be at least 1.");
+    }
+}
@@ -0,0 +1,65 @@
+/// Provenance written in front of every line of a part, so a line quoted
+/// from a part can be traced back to the input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinePrefix {
+    /// The original line number, zero-padded to the width of the largest
+    /// line number in the split: `001234: text`.
+    LineNumber,
+    /// For SRT / WebVTT input, the start time of the cue a text line belongs
+    /// to: `[00:01:23,456] text`. Cue numbers, timing lines and blank lines
+    /// are left as they are.
+    CueStart,
+}
+
+/// Applies a [`LinePrefix`] to lines in input order. Cue prefixes need every
+/// line from the start of the file, so the state must see them all.
+pub(crate) struct Prefixer {
+    kind: LinePrefix,
+    width: usize,
+    cue_start: Option<String>,
+}
+
+impl Prefixer {
+    /// `last_line` is the largest line number that can be written.
+    pub(crate) fn new(kind: LinePrefix, last_line: usize) -> Self {
+        Prefixer {
+            kind,
+            width: last_line.max(1).to_string().len(),
+            cue_start: None,
+        }
+    }
+
+    /// The prefix for a line, judged on its original text (so a timing line
+    /// is recognised even if a transform later rewrites or drops it).
+    pub(crate) fn prefix(&mut self, line_number: usize, line: &str) -> Option<String> {
+        match self.kind {
+            LinePrefix::LineNumber => {
+                Some(format!("{:0width$}: ", line_number, width = self.width))
+            }
+            LinePrefix::CueStart => {
+                if line.trim().is_empty() {
+                    self.cue_start = None;
+                    return None;
+                }
+                if let Some(start) = timing_start(line) {
+                    self.cue_start = Some(start.to_string());
+                    return None;
+                }
+                self.cue_start.as_ref().map(|start| format!("[{}] ", start))
+            }
+        }
+    }
+}
+
+/// Start timestamp of an SRT / WebVTT timing line
+/// (`00:01:23,456 --> 00:01:25,000`, `01:23.456 --> ...`).
+fn timing_start(line: &str) -> Option<&str> {
+    let (start, _) = line.split_once("-->")?;
+    let start = start.trim();
+    let looks_like_time = !start.is_empty()
+        && start.contains(':')
+        && start
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, ':' | ',' | '.'));
+    looks_like_time.then_some(start)
+}
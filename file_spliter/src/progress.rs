@@ -0,0 +1,127 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Snapshot handed to the progress callback while a split runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SplitProgress {
+    /// Bytes of input consumed so far (line terminators counted as one byte).
+    pub bytes_read: u64,
+    /// Size of the input file (for a parallel split: the bytes the parts
+    /// copy out of it).
+    pub total_bytes: u64,
+    /// Lines processed so far (a parallel split only knows this at the end).
+    pub lines_read: usize,
+    /// 1-based number of the part written to most recently.
+    pub current_part: Option<usize>,
+}
+
+impl SplitProgress {
+    /// Progress between 0.0 and 1.0.
+    pub fn fraction(&self) -> f32 {
+        if self.total_bytes == 0 {
+            return 1.0;
+        }
+        (self.bytes_read as f64 / self.total_bytes as f64).min(1.0) as f32
+    }
+}
+
+/// Called from the splitting thread every few thousand lines and once at the
+/// end.
+pub type ProgressCallback = Arc<dyn Fn(&SplitProgress) + Send + Sync>;
+
+/// Shared flag that stops a running split from another thread. The split
+/// returns an error and removes the parts it created.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// How many lines pass between two progress reports / cancellation checks.
+pub(crate) const REPORT_EVERY: usize = 4096;
+
+/// Keeps the running totals for one split and decides when to report.
+pub(crate) struct ProgressTracker<'a> {
+    callback: Option<&'a ProgressCallback>,
+    cancel: Option<&'a CancelToken>,
+    state: SplitProgress,
+}
+
+impl<'a> ProgressTracker<'a> {
+    pub(crate) fn new(
+        callback: Option<&'a ProgressCallback>,
+        cancel: Option<&'a CancelToken>,
+        total_bytes: u64,
+    ) -> Self {
+        ProgressTracker {
+            callback,
+            cancel,
+            state: SplitProgress {
+                bytes_read: 0,
+                total_bytes,
+                lines_read: 0,
+                current_part: None,
+            },
+        }
+    }
+
+    /// Accounts for lines that were skipped without being read, e.g. after
+    /// seeking with a line index.
+    pub(crate) fn skip(&mut self, lines: usize, bytes: u64) {
+        self.state.lines_read += lines;
+        self.state.bytes_read += bytes;
+    }
+
+    /// Records one processed line, `line_bytes` long in the input (without
+    /// its newline); reports and checks for cancellation every
+    /// [`REPORT_EVERY`] lines.
+    pub(crate) fn line_done(
+        &mut self,
+        line_bytes: usize,
+        part: Option<usize>,
+    ) -> Result<(), String> {
+        self.state.lines_read += 1;
+        self.state.bytes_read += line_bytes as u64 + 1;
+        if part.is_some() {
+            self.state.current_part = part;
+        }
+
+        if self.state.lines_read.is_multiple_of(REPORT_EVERY) {
+            self.check_cancelled()?;
+            self.report();
+        }
+        Ok(())
+    }
+
+    pub(crate) fn check_cancelled(&self) -> Result<(), String> {
+        match self.cancel {
+            Some(token) if token.is_cancelled() => Err("Split cancelled.".to_string()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Sends the final report with the byte count pinned to the file size.
+    pub(crate) fn finish(&mut self) {
+        self.state.bytes_read = self.state.total_bytes;
+        self.report();
+    }
+
+    fn report(&self) {
+        if let Some(callback) = self.callback {
+            let mut state = self.state;
+            state.bytes_read = state.bytes_read.min(state.total_bytes);
+            callback(&state);
+        }
+    }
+}
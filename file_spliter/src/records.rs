@@ -0,0 +1,158 @@
+/// What a chunk size, share or round-robin turn is counted in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SplitUnit {
+    #[default]
+    Lines,
+    /// Blank-line separated blocks, e.g. SRT cues.
+    Cues,
+    /// CSV records; a quoted field may span several lines.
+    CsvRecords,
+}
+
+/// Finds where records start as lines are fed in order.
+///
+/// Cues are blank-line separated blocks: each block owns the blank lines that
+/// follow it, and leading blank lines belong to the first block. A CSV record
+/// ends on the first line where every quoted field has been closed, so a
+/// quoted field containing newlines is never cut.
+#[derive(Debug, Clone)]
+pub struct RecordTracker {
+    unit: SplitUnit,
+    previous_blank: bool,
+    in_quotes: bool,
+    started: bool,
+    record: usize,
+}
+
+impl RecordTracker {
+    pub fn new(unit: SplitUnit) -> Self {
+        RecordTracker {
+            unit,
+            previous_blank: true,
+            in_quotes: false,
+            started: false,
+            record: 0,
+        }
+    }
+
+    /// Feeds the next line and returns whether it starts a new record.
+    pub fn starts_record(&mut self, line: &str) -> bool {
+        let starts = match self.unit {
+            SplitUnit::Lines => true,
+            SplitUnit::Cues => {
+                let blank = line.trim().is_empty();
+                let starts = self.previous_blank && !blank;
+                self.previous_blank = blank;
+                starts
+            }
+            SplitUnit::CsvRecords => {
+                let starts = !self.in_quotes;
+                // An escaped quote ("") flips the state twice, so counting works
+                if line.matches('"').count() % 2 == 1 {
+                    self.in_quotes = !self.in_quotes;
+                }
+                starts
+            }
+        };
+
+        if starts {
+            if self.started {
+                self.record += 1;
+            }
+            self.started = true;
+        }
+        starts
+    }
+
+    /// 0-based number of the record the last line fed belongs to.
+    pub fn record(&self) -> usize {
+        self.record
+    }
+
+    /// Whether the input so far ends inside a quoted CSV field.
+    pub fn in_quotes(&self) -> bool {
+        self.in_quotes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SplitMode, SplitPlanner, split_str};
+
+    /// `(starts_record, record)` after each line.
+    fn feed(unit: SplitUnit, input: &str) -> Vec<(bool, usize)> {
+        let mut tracker = RecordTracker::new(unit);
+        input
+            .lines()
+            .map(|line| (tracker.starts_record(line), tracker.record()))
+            .collect()
+    }
+
+    #[test]
+    fn csv_quoted_newlines() {
+        let input = "1,\"two\nlines\",x\n2,plain\n3,\"a\n\nb\"\n";
+        assert_eq!(
+            feed(SplitUnit::CsvRecords, input),
+            [
+                (true, 0),
+                (false, 0),
+                (true, 1),
+                (true, 2),
+                (false, 2),
+                (false, 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn csv_escaped_quotes() {
+        // "" inside a field does not close it; the odd quote on line 2 does
+        let input = "1,\"say \"\"hi\"\"\nthere\",x\n2,\"\"\"quoted\"\"\"\n";
+        let mut tracker = RecordTracker::new(SplitUnit::CsvRecords);
+        let starts: Vec<bool> = input.lines().map(|l| tracker.starts_record(l)).collect();
+        assert_eq!(starts, [true, false, true]);
+        assert!(!tracker.in_quotes());
+
+        tracker.starts_record("3,\"open");
+        assert!(tracker.in_quotes());
+    }
+
+    #[test]
+    fn cues_own_their_blank_lines() {
+        let input =
+            "\n1\n00:00:01,000 --> 00:00:02,000\nHi\n\n\n2\n00:00:03,000 --> 00:00:04,000\nBye\n";
+        let records: Vec<usize> = feed(SplitUnit::Cues, input).iter().map(|r| r.1).collect();
+        assert_eq!(records, [0, 0, 0, 0, 0, 0, 1, 1, 1]);
+    }
+
+    #[test]
+    fn csv_header_repeats_in_every_part() {
+        let input = "id,note\n1,\"a\nb\"\n2,c\n3,\"d\"\"e\"\n4,f\n";
+        let mode = SplitMode::Auto {
+            chunk_size: 2,
+            unit: SplitUnit::CsvRecords,
+        };
+        let plan = SplitPlanner::new("notes.csv", mode)
+            .with_header_lines(1)
+            .build_from_bytes(input.as_bytes())
+            .unwrap();
+        let parts = split_str(input, &plan.configs, plan.options).unwrap();
+        assert_eq!(
+            parts,
+            ["id,note\n1,\"a\nb\"\n2,c\n", "id,note\n3,\"d\"\"e\"\n4,f\n"]
+        );
+    }
+
+    #[test]
+    fn unbalanced_quotes_are_rejected() {
+        let mode = SplitMode::Auto {
+            chunk_size: 2,
+            unit: SplitUnit::CsvRecords,
+        };
+        let error = SplitPlanner::new("notes.csv", mode)
+            .build_from_bytes(b"1,\"open\n2,b\n")
+            .unwrap_err();
+        assert_eq!(error, "CSV ends inside a quoted field (unbalanced quotes).");
+    }
+}
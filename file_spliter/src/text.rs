@@ -0,0 +1,547 @@
+use crate::json::PartWriter;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+
+/// How far a boundary may move from the target size, as a fraction of it,
+/// to land on a paragraph or sentence end.
+const SLACK: f64 = 0.25;
+
+/// Target size of each part for [`split_text`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextChunkSize {
+    Lines(usize),
+    Chars(usize),
+    /// Estimated LLM tokens: about four characters per token for words in
+    /// Latin script, two for scripts written without spaces such as Thai.
+    Tokens(usize),
+}
+
+impl TextChunkSize {
+    fn target(&self) -> usize {
+        match *self {
+            TextChunkSize::Lines(n) | TextChunkSize::Chars(n) | TextChunkSize::Tokens(n) => n,
+        }
+    }
+
+    fn measure(&self, text: &str) -> usize {
+        match self {
+            TextChunkSize::Lines(_) => 1,
+            TextChunkSize::Chars(_) => text.chars().count(),
+            TextChunkSize::Tokens(_) => estimate_tokens(text),
+        }
+    }
+}
+
+/// How good a place to cut the end of a piece is; stronger is better.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Boundary {
+    /// Inside a word too long for one part (e.g. a run of Thai text).
+    Char,
+    Word,
+    /// A line break inside a sentence (wrapped prose).
+    Line,
+    Sentence,
+    /// A blank line follows.
+    Paragraph,
+}
+
+/// A run of text that is never cut, with the boundary that follows it.
+struct Piece {
+    text: String,
+    size: usize,
+    after: Boundary,
+}
+
+/// Splits plain prose into parts of about `size`, moving every cut to the
+/// nearest paragraph break (blank line) or, failing that, sentence end.
+///
+/// A cut may land up to a quarter of the target before or after it. Sentence
+/// ends are `.`, `!`, `?` and `…` followed by a space, `。！？`, and, in Thai,
+/// the space between two Thai words. A sentence longer than a whole part is
+/// cut between words. `part_path` works as in
+/// [`split_json_lines`](crate::split_json_lines).
+pub fn split_text<P, F>(input_path: P, size: TextChunkSize, part_path: F) -> Result<String, String>
+where
+    P: AsRef<Path>,
+    F: FnMut(usize) -> String,
+{
+    if size.target() == 0 {
+        return Err("Text chunk size must be at least 1.".to_string());
+    }
+    let input_file = File::open(input_path.as_ref()).map_err(|e| format!("Open error: {}", e))?;
+
+    let mut parts = PartWriter::new(part_path);
+    let result = write_text(BufReader::new(input_file), size, &mut parts);
+    if result.is_err() {
+        parts.cleanup();
+    }
+    result?;
+
+    if parts.created.is_empty() {
+        return Err("Input file has no text.".to_string());
+    }
+    Ok(format!(
+        "Success! Wrote {} parts at paragraph and sentence boundaries.",
+        parts.created.len()
+    ))
+}
+
+// =========================================================================
+// HELPER 1: CHUNKING
+// Buffers pieces until everything up to the largest allowed part is known,
+// then cuts at the best boundary in the window around the target.
+// =========================================================================
+struct Chunker {
+    size: TextChunkSize,
+    low: usize,
+    high: usize,
+    buffer: VecDeque<Piece>,
+    buffered: usize,
+}
+
+impl Chunker {
+    fn new(size: TextChunkSize) -> Self {
+        let target = size.target() as f64;
+        Chunker {
+            size,
+            low: ((target * (1.0 - SLACK)).floor() as usize).max(1),
+            high: ((target * (1.0 + SLACK)).ceil() as usize).max(1),
+            buffer: VecDeque::new(),
+            buffered: 0,
+        }
+    }
+
+    fn push<F: FnMut(usize) -> String>(
+        &mut self,
+        piece: Piece,
+        parts: &mut PartWriter<F>,
+    ) -> Result<(), String> {
+        // A piece bigger than a whole part is broken up first
+        let pieces = if piece.size > self.size.target() {
+            break_piece(piece, self.size)
+        } else {
+            vec![piece]
+        };
+        for piece in pieces {
+            self.buffered += piece.size;
+            self.buffer.push_back(piece);
+            while self.buffered > self.high {
+                self.emit(parts)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Marks the end of the last piece as a paragraph break.
+    fn end_paragraph(&mut self, blank_line: &str) {
+        if let Some(last) = self.buffer.back_mut() {
+            last.text.push_str(blank_line);
+            last.text.push('\n');
+            last.after = Boundary::Paragraph;
+            if let TextChunkSize::Lines(_) = self.size {
+                last.size += 1;
+                self.buffered += 1;
+            }
+        }
+    }
+
+    fn finish<F: FnMut(usize) -> String>(
+        &mut self,
+        parts: &mut PartWriter<F>,
+    ) -> Result<(), String> {
+        while !self.buffer.is_empty() {
+            if self.buffered <= self.high {
+                let count = self.buffer.len();
+                self.write_part(count, parts)?;
+            } else {
+                self.emit(parts)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes one part, cut at the strongest boundary whose size falls in
+    /// the allowed window, the one nearest the target among equals.
+    fn emit<F: FnMut(usize) -> String>(&mut self, parts: &mut PartWriter<F>) -> Result<(), String> {
+        let target = self.size.target();
+        let mut best: Option<(Boundary, usize, usize)> = None;
+        let mut fallback = 0;
+        let mut total = 0;
+
+        for (i, piece) in self.buffer.iter().enumerate() {
+            total += piece.size;
+            if total > self.high {
+                break;
+            }
+            fallback = i;
+            if total < self.low {
+                continue;
+            }
+            let distance = total.abs_diff(target);
+            let better = match best {
+                None => true,
+                Some((after, best_distance, _)) => {
+                    piece.after > after || (piece.after == after && distance < best_distance)
+                }
+            };
+            if better {
+                best = Some((piece.after, distance, i));
+            }
+        }
+
+        let last = best.map_or(fallback, |(_, _, i)| i);
+        self.write_part(last + 1, parts)
+    }
+
+    fn write_part<F: FnMut(usize) -> String>(
+        &mut self,
+        count: usize,
+        parts: &mut PartWriter<F>,
+    ) -> Result<(), String> {
+        let mut text = String::new();
+        for piece in self.buffer.drain(..count) {
+            self.buffered -= piece.size;
+            text.push_str(&piece.text);
+        }
+        let text = text.trim_end();
+        if text.is_empty() {
+            return Ok(());
+        }
+
+        parts.start_part(b"")?;
+        if let Some(writer) = parts.current.as_mut() {
+            writeln!(writer, "{}", text).map_err(|e| format!("Write error: {}", e))?;
+        }
+        parts.finish_part(b"")
+    }
+}
+
+fn write_text<R: BufRead, F: FnMut(usize) -> String>(
+    reader: R,
+    size: TextChunkSize,
+    parts: &mut PartWriter<F>,
+) -> Result<(), String> {
+    let mut chunker = Chunker::new(size);
+
+    for (index, line_result) in reader.lines().enumerate() {
+        let line = line_result.map_err(|e| format!("Read error at line {}: {}", index + 1, e))?;
+        if line.trim().is_empty() {
+            chunker.end_paragraph(&line);
+            continue;
+        }
+
+        let ends_sentence = ends_with_terminator(&line);
+        let line_end = if ends_sentence {
+            Boundary::Sentence
+        } else {
+            Boundary::Line
+        };
+
+        if let TextChunkSize::Lines(_) = size {
+            let piece = Piece {
+                text: line + "\n",
+                size: 1,
+                after: line_end,
+            };
+            chunker.push(piece, parts)?;
+            continue;
+        }
+
+        let cuts = sentence_ends(&line);
+        let mut start = 0;
+        for (i, &end) in cuts.iter().chain([line.len()].iter()).enumerate() {
+            if end <= start {
+                continue;
+            }
+            let last = i == cuts.len();
+            let mut text = line[start..end].to_string();
+            if last {
+                text.push('\n');
+            }
+            let piece = Piece {
+                size: size.measure(&text),
+                text,
+                after: if last { line_end } else { Boundary::Sentence },
+            };
+            chunker.push(piece, parts)?;
+            start = end;
+        }
+    }
+
+    chunker.finish(parts)
+}
+
+// =========================================================================
+// HELPER 2: SENTENCES
+// =========================================================================
+const TERMINATORS: [char; 4] = ['.', '!', '?', '…'];
+const FULL_WIDTH_TERMINATORS: [char; 3] = ['。', '！', '？'];
+const CLOSERS: [char; 7] = ['"', '\'', '”', '’', ')', ']', '»'];
+
+fn is_thai(c: char) -> bool {
+    ('\u{0E00}'..='\u{0E7F}').contains(&c)
+}
+
+/// Whether a line ends a sentence. A line ending in Thai does: like a space,
+/// the line break separates Thai sentences.
+fn ends_with_terminator(line: &str) -> bool {
+    line.trim_end().trim_end_matches(CLOSERS).ends_with(|c| {
+        TERMINATORS.contains(&c) || FULL_WIDTH_TERMINATORS.contains(&c) || is_thai(c)
+    })
+}
+
+/// Byte offsets inside `line` where a sentence ends (after its trailing
+/// spaces). The end of the line itself is not included.
+fn sentence_ends(line: &str) -> Vec<usize> {
+    let chars: Vec<(usize, char)> = line.char_indices().collect();
+    let mut ends = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let (_, c) = chars[i];
+        let after_closers = |mut j: usize| {
+            while j < chars.len() && CLOSERS.contains(&chars[j].1) {
+                j += 1;
+            }
+            j
+        };
+        let after_spaces = |mut j: usize| {
+            while j < chars.len() && chars[j].1.is_whitespace() {
+                j += 1;
+            }
+            j
+        };
+
+        let end = if TERMINATORS.contains(&c) {
+            // "end." followed by a space; "3.14" or "e.g.x" are not ends
+            let j = after_closers(i + 1);
+            (j < chars.len() && chars[j].1.is_whitespace()).then(|| after_spaces(j))
+        } else if FULL_WIDTH_TERMINATORS.contains(&c) {
+            Some(after_spaces(after_closers(i + 1)))
+        } else if c.is_whitespace() && i > 0 && is_thai(chars[i - 1].1) {
+            // Thai separates sentences with a space, not punctuation
+            let j = after_spaces(i);
+            (j < chars.len() && is_thai(chars[j].1)).then_some(j)
+        } else {
+            None
+        };
+
+        match end {
+            Some(j) if j < chars.len() => {
+                ends.push(chars[j].0);
+                i = j;
+            }
+            Some(_) => break,
+            None => i += 1,
+        }
+    }
+    ends
+}
+
+// =========================================================================
+// HELPER 3: OVERSIZED PIECES
+// =========================================================================
+
+/// Breaks a piece larger than a part between words, and a word larger than
+/// a part (e.g. a long run of Thai) between characters. The last fragment
+/// keeps the piece's own boundary.
+fn break_piece(piece: Piece, size: TextChunkSize) -> Vec<Piece> {
+    let target = size.target();
+    let mut fragments: Vec<Piece> = Vec::new();
+
+    for word in piece.text.split_inclusive(char::is_whitespace) {
+        let word_size = size.measure(word);
+        if word_size <= target {
+            fragments.push(Piece {
+                text: word.to_string(),
+                size: word_size,
+                after: Boundary::Word,
+            });
+            continue;
+        }
+
+        let mut current = String::new();
+        for c in word.chars() {
+            current.push(c);
+            if size.measure(&current) > target {
+                current.pop();
+                fragments.push(Piece {
+                    size: size.measure(&current),
+                    text: std::mem::take(&mut current),
+                    after: Boundary::Char,
+                });
+                current.push(c);
+            }
+        }
+        if !current.is_empty() {
+            fragments.push(Piece {
+                size: size.measure(&current),
+                text: current,
+                after: Boundary::Char,
+            });
+        }
+    }
+
+    if let Some(last) = fragments.last_mut() {
+        last.after = piece.after;
+    }
+    fragments
+}
+
+/// Rough LLM token count: a token per ~4 characters of a Latin word, per
+/// ~2 characters of other scripts, and per punctuation mark.
+fn estimate_tokens(text: &str) -> usize {
+    let mut tokens = 0;
+    let mut latin_run: usize = 0;
+    let mut other: usize = 0;
+
+    for c in text.chars() {
+        if c.is_ascii_alphanumeric() {
+            latin_run += 1;
+            continue;
+        }
+        tokens += latin_run.div_ceil(4);
+        latin_run = 0;
+        if c.is_whitespace() {
+            continue;
+        }
+        if c.is_ascii() {
+            tokens += 1;
+        } else {
+            other += 1;
+        }
+    }
+    tokens + latin_run.div_ceil(4) + other.div_ceil(2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Runs `write_text` on `input` and returns the parts written.
+    fn parts(input: &str, size: TextChunkSize) -> Vec<String> {
+        static RUN: AtomicUsize = AtomicUsize::new(0);
+        let dir = std::env::temp_dir().join(format!(
+            "file_spliter_text_{}_{}",
+            std::process::id(),
+            RUN.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&dir).unwrap();
+
+        let mut writer = PartWriter::new(|i| dir.join(format!("{}.txt", i)).display().to_string());
+        write_text(input.as_bytes(), size, &mut writer).unwrap();
+        let written = writer
+            .created
+            .iter()
+            .map(|path| fs::read_to_string(path).unwrap())
+            .collect();
+        fs::remove_dir_all(&dir).unwrap();
+        written
+    }
+
+    fn texts(pieces: &[Piece]) -> Vec<&str> {
+        pieces.iter().map(|p| p.text.as_str()).collect()
+    }
+
+    #[test]
+    fn cuts_at_a_paragraph_within_the_slack() {
+        // A target of 40 allows parts of 30 to 50 characters
+        let input =
+            "One two three four five six seven.\n\nEight nine ten eleven twelve. Thirteen.\n";
+        assert_eq!(
+            parts(input, TextChunkSize::Chars(40)),
+            [
+                "One two three four five six seven.\n",
+                "Eight nine ten eleven twelve. Thirteen.\n"
+            ]
+        );
+    }
+
+    #[test]
+    fn cuts_at_the_sentence_nearest_the_target() {
+        // Sentences of 13 characters: 39 is nearest a target of 40
+        let sentence = "Aaaa bbbb c. ";
+        let input = sentence.repeat(6);
+        let part = format!("{}\n", sentence.repeat(3).trim_end());
+        assert_eq!(
+            parts(&input, TextChunkSize::Chars(40)),
+            [part.clone(), part]
+        );
+    }
+
+    #[test]
+    fn line_targets_keep_paragraphs_together() {
+        // A target of 2 lines allows 1 to 3, blank lines included
+        let input = "a\nb\n\nc\nd\n\ne\nf\n";
+        assert_eq!(
+            parts(input, TextChunkSize::Lines(2)),
+            ["a\nb\n", "c\nd\n", "e\nf\n"]
+        );
+        // Without a paragraph break in the window, the cut is nearest the target
+        assert_eq!(
+            parts("a\nb\nc\nd\n", TextChunkSize::Lines(2)),
+            ["a\nb\n", "c\nd\n"]
+        );
+    }
+
+    #[test]
+    fn thai_sentence_breaks() {
+        let line = "สวัสดีครับ วันนี้อากาศดี ok then";
+        let ends = sentence_ends(line);
+        assert_eq!(ends, [line.find("วันนี้").unwrap()]);
+        assert!(ends_with_terminator("ภาษาไทย"));
+        assert!(ends_with_terminator("Done.\")  "));
+        assert!(!ends_with_terminator("wrapped line"));
+    }
+
+    #[test]
+    fn sentence_ends_skip_numbers_and_abbreviations() {
+        let line = "Pi is 3.14, e.g.x works. Next!\"  Last。続き";
+        let ends: Vec<&str> = sentence_ends(line).iter().map(|&i| &line[i..]).collect();
+        assert_eq!(ends, ["Next!\"  Last。続き", "Last。続き", "続き"]);
+    }
+
+    #[test]
+    fn break_piece_splits_long_words() {
+        let piece = Piece {
+            text: "abcdefghijkl mn".to_string(),
+            size: 15,
+            after: Boundary::Sentence,
+        };
+        let fragments = break_piece(piece, TextChunkSize::Chars(5));
+        assert_eq!(texts(&fragments), ["abcde", "fghij", "kl ", "mn"]);
+        let after: Vec<Boundary> = fragments.iter().map(|f| f.after).collect();
+        assert_eq!(
+            after,
+            [
+                Boundary::Char,
+                Boundary::Char,
+                Boundary::Char,
+                Boundary::Sentence
+            ]
+        );
+        assert!(fragments.iter().all(|f| f.size <= 5));
+    }
+
+    #[test]
+    fn long_thai_run_is_cut_between_characters() {
+        let input = "ก".repeat(25);
+        let written = parts(&input, TextChunkSize::Chars(10));
+        let sizes: Vec<usize> = written
+            .iter()
+            .map(|p| p.trim_end().chars().count())
+            .collect();
+        assert_eq!(sizes, [10, 10, 5]);
+    }
+
+    #[test]
+    fn token_estimates() {
+        assert_eq!(estimate_tokens("hello world"), 4);
+        assert_eq!(estimate_tokens("สวัสดี"), 3);
+        assert_eq!(estimate_tokens("ok, ไทย!"), 5);
+    }
+}
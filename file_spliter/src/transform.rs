@@ -0,0 +1,86 @@
+use regex::Regex;
+use std::sync::{Arc, Mutex};
+
+/// Rewrites or drops lines as they pass through a split.
+///
+/// Ranges always refer to the original line numbers: a dropped line still
+/// counts, it is just not written.
+pub trait LineTransform: Send {
+    /// Returns the line to write, or `None` to drop it. `line_number` is the
+    /// 1-based number of the line in the input.
+    fn transform(&mut self, line_number: usize, line: String) -> Option<String>;
+}
+
+/// A transform shared with [`SplitOptions`](crate::SplitOptions). It is
+/// locked once for the whole split, so state (e.g. "was the last line
+/// blank?") carries from line to line.
+pub type SharedTransform = Arc<Mutex<dyn LineTransform>>;
+
+/// Keeps or drops lines that match a regular expression, like `grep` /
+/// `grep -v`.
+#[derive(Debug, Clone)]
+pub struct RegexFilter {
+    pattern: Regex,
+    keep_matches: bool,
+}
+
+impl RegexFilter {
+    /// Keeps only the lines matching `pattern`.
+    pub fn include(pattern: &str) -> Result<Self, String> {
+        Self::new(pattern, true)
+    }
+
+    /// Drops the lines matching `pattern`.
+    pub fn exclude(pattern: &str) -> Result<Self, String> {
+        Self::new(pattern, false)
+    }
+
+    fn new(pattern: &str, keep_matches: bool) -> Result<Self, String> {
+        let pattern =
+            Regex::new(pattern).map_err(|e| format!("Invalid pattern '{}': {}", pattern, e))?;
+        Ok(RegexFilter {
+            pattern,
+            keep_matches,
+        })
+    }
+}
+
+impl LineTransform for RegexFilter {
+    fn transform(&mut self, _line_number: usize, line: String) -> Option<String> {
+        (self.pattern.is_match(&line) == self.keep_matches).then_some(line)
+    }
+}
+
+/// Strips leading and trailing whitespace.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TrimWhitespace;
+
+impl LineTransform for TrimWhitespace {
+    fn transform(&mut self, _line_number: usize, line: String) -> Option<String> {
+        let trimmed = line.trim();
+        if trimmed.len() == line.len() {
+            return Some(line);
+        }
+        Some(trimmed.to_string())
+    }
+}
+
+/// Turns every run of blank (or whitespace-only) lines into a single empty
+/// line.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CollapseBlankLines {
+    previous_blank: bool,
+}
+
+impl LineTransform for CollapseBlankLines {
+    fn transform(&mut self, _line_number: usize, line: String) -> Option<String> {
+        let blank = line.trim().is_empty();
+        let repeated = blank && self.previous_blank;
+        self.previous_blank = blank;
+        match (repeated, blank) {
+            (true, _) => None,
+            (false, true) => Some(String::new()),
+            (false, false) => Some(line),
+        }
+    }
+}
@@ -1,20 +1,89 @@
 mod slint_ui;
 mod tasks;
 
-use file_spliter::split_file;
+use file_spliter::{
+    CancelToken, CollapseBlankLines, LineIndex, LinePrefix, PartNaming, RegexFilter,
+    SharedTransform, SplitMode, SplitPlanner, SplitProgress, SplitUnit, TrimWhitespace,
+    parse_json_limit, parse_range, parse_shares, parse_text_size, split_file_async,
+};
 use std::env;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
 
+use file_spliter::{JsonChunkLimit, TextChunkSize};
 use std::process;
-use tasks::{SplitMode, build_split_plan};
-use youtube_subtitle_manager::{download_subtitle, extract_id, scan_subtitles};
+use tasks::{run_json_split, run_text_split};
+use youtube_subtitle_manager::{
+    DownloadOptions, MergeOptions, Retime, SubtitleFormat, SyncPoint, TimingOptions,
+    convert_subtitle, download_subtitle_with_options, extract_id, fix_subtitle, parse_time,
+    retime_subtitle, scan_subtitles,
+};
+
+/// Output options shared by every split command.
+#[derive(Default)]
+struct SplitFlags {
+    parallel: bool,
+    line_prefix: Option<LinePrefix>,
+    transforms: Vec<SharedTransform>,
+    naming: Option<PartNaming>,
+    overlap: usize,
+}
+
+impl SplitFlags {
+    /// Whether `--name` is the only option given.
+    fn names_only(&self) -> bool {
+        !self.parallel
+            && self.line_prefix.is_none()
+            && self.transforms.is_empty()
+            && self.overlap == 0
+    }
+}
 
 // slint imports removed as they are now handled in slint_ui.rs
 
 enum AppMode {
     Ui,
-    Download { video_id: String, lang: String },
-    Scan { video_id: String },
-    Split { input_path: String, mode: SplitMode },
+    Download {
+        video_id: String,
+        lang: String,
+        output_path: Option<String>,
+        options: DownloadOptions,
+    },
+    Scan {
+        video_id: String,
+    },
+    Convert {
+        input_path: String,
+        output_path: String,
+        options: DownloadOptions,
+    },
+    Fix {
+        input_path: String,
+        output_path: String,
+        options: TimingOptions,
+    },
+    Retime {
+        input_path: String,
+        output_path: String,
+        retime: Retime,
+    },
+    Split {
+        planner: SplitPlanner,
+        flags: SplitFlags,
+    },
+    JsonSplit {
+        input_path: String,
+        limit: JsonChunkLimit,
+        naming: PartNaming,
+    },
+    TextSplit {
+        input_path: String,
+        size: TextChunkSize,
+        naming: PartNaming,
+    },
+    Index {
+        input_path: String,
+    },
     Help,
 }
 
@@ -25,10 +94,57 @@ fn print_usage(program_name: &str) {
         "  nth      {} <file> <size>     | Split file into chunks of <size> lines",
         program_name
     );
+    println!("           [--cues | --csv]        | Count cues or CSV records instead of lines");
+    println!("           [--header <n>]          | Repeat the first <n> lines in every part");
     println!(
         "  manual   {} <file> <range>... | Split specific ranges (e.g. 1-100 200-300)",
         program_name
     );
+    println!("           [--strict] [--header <n>] | Fail on overlaps, gaps or ranges past EOF");
+    println!(
+        "  ratio    {} <file> <shares>   | Split by shares (e.g. 70,20,10 or 50%,50%)",
+        program_name
+    );
+    println!(
+        "           [--seed <n>]            | Shuffle lines first (also takes --cues, --csv, --header)"
+    );
+    println!(
+        "  interleave {} <file> <parts> | Deal lines round-robin into <parts> parts (takes --cues, --csv, --header)",
+        program_name
+    );
+    println!(
+        "  markdown {} <file> [level]    | Split Markdown at headings of [level] (default 2)",
+        program_name
+    );
+    println!(
+        "           [--parents]             | Repeat the parent headings at the top of each part"
+    );
+    println!(
+        "  json     {} <file> <limit>    | Split .json arrays or .jsonl by records (100) or size (5MB)",
+        program_name
+    );
+    println!(
+        "  text     {} <file> <size>     | Split prose at paragraph/sentence ends: lines (200), 4000c or 1000t",
+        program_name
+    );
+    println!(
+        "  --parallel                       | Copy parts concurrently (nth, manual, ratio, markdown)"
+    );
+    println!(
+        "  --prefix <lines | time>          | Prefix lines with their original number or cue start time"
+    );
+    println!(
+        "  --name <template>                | Name parts from {{stem}}, {{n}}, {{ext}}, {{title}} (e.g. {{stem}}_{{n}}.{{ext}}); also json, text"
+    );
+    println!(
+        "  --overlap <n>                    | Repeat the last <n> units of each part at the start of the next (nth, ratio)"
+    );
+    println!("  --include / --exclude <regex>    | Keep only / drop matching lines (repeatable)");
+    println!("  --trim, --collapse-blank         | Trim whitespace, squeeze runs of blank lines");
+    println!(
+        "  index    {} <file>            | Build a line index so later range splits seek directly",
+        program_name
+    );
     println!(
         "  scan     {} <video_id>        | List available subtitle languages",
         program_name
@@ -37,13 +153,357 @@ fn print_usage(program_name: &str) {
         "  download {} <video_id> [lang] | Download YouTube subtitles (default lang: en)",
         program_name
     );
+    println!(
+        "           [second_lang]           | Add a second language line under each cue, e.g. download <id> th en"
+    );
+    println!("           [--output <file>]       | Save to <file>; its extension picks the format");
+    println!(
+        "           [--format <fmt>]        | srt, vtt, sbv, ass, ttml, lrc, txt, md or json (default: from --output, else srt)"
+    );
+    println!(
+        "           [--cue-settings <s>]    | WebVTT cue settings, e.g. \"line:85% align:center\""
+    );
+    println!(
+        "           [--merge]               | Merge caption fragments into sentence-sized cues"
+    );
+    println!(
+        "           [--max-chars <n>] [--max-duration <s>] | Merged cue limits (default: 84 chars, 7 s)"
+    );
+    println!("           [--fix-timing]          | Remove overlaps, too short and empty cues");
+    println!(
+        "           [--min-duration <s>] [--min-gap <s>] | Fixed cue limits (default: 0.5 s, 0.04 s)"
+    );
+    println!(
+        "  convert  {} <input> <output>  | Convert a .srt, .vtt, .sbv or .json subtitle file",
+        program_name
+    );
+    println!(
+        "           [--format <fmt>]        | Output format (default: from <output>); also takes --cue-settings, --merge, --fix-timing"
+    );
+    println!(
+        "  fix      {} <input> [output]  | Fix overlapping, too short and empty cues in a subtitle file",
+        program_name
+    );
+    println!(
+        "           [--min-duration <s>] [--min-gap <s>] | Cue limits (default: 0.5 s, 0.04 s); output defaults to <input>.fixed.<ext>"
+    );
+    println!(
+        "  retime   {} <input> [output]  | Move the cues of a subtitle file; output defaults to <input>.retimed.<ext>",
+        program_name
+    );
+    println!("           --shift <time>          | Add an offset, e.g. 2.5, -00:00:12,000");
+    println!("           --fps <from>:<to>       | Convert between frame rates, e.g. 23.976:25");
+    println!(
+        "           --sync <n>=<time> --sync <n>=<time> | Make cue n start at <time>, e.g. 10=00:01:05 900=01:12:30"
+    );
     println!(
         "  help     {}                   | Show this help message",
         program_name
     );
 }
 
+/// Removes `flag` from `args`, returning whether it was present.
+fn take_flag(args: &mut Vec<String>, flag: &str) -> bool {
+    let before = args.len();
+    args.retain(|a| a != flag);
+    args.len() != before
+}
+
+/// Removes `flag <value>` from `args`, returning the value if present.
+fn take_value(args: &mut Vec<String>, flag: &str) -> Result<Option<String>, String> {
+    match args.iter().position(|a| a == flag) {
+        Some(i) if i + 1 < args.len() => {
+            let value = args.remove(i + 1);
+            args.remove(i);
+            Ok(Some(value))
+        }
+        Some(_) => Err(format!("Missing value after '{}'", flag)),
+        None => Ok(None),
+    }
+}
+
+/// Removes the timing limits `--min-duration` and `--min-gap`, returning
+/// them when either is given.
+fn take_timing_options(args: &mut Vec<String>) -> Result<Option<TimingOptions>, String> {
+    let mut seconds = |flag: &str| {
+        take_value(args, flag)?
+            .map(|s| {
+                s.parse::<f64>()
+                    .map_err(|_| format!("Invalid {} '{}'", flag, s))
+            })
+            .transpose()
+    };
+    let min_duration = seconds("--min-duration")?;
+    let min_gap = seconds("--min-gap")?;
+    if min_duration.is_none() && min_gap.is_none() {
+        return Ok(None);
+    }
+    let defaults = TimingOptions::default();
+    Ok(Some(TimingOptions {
+        min_duration: min_duration.unwrap_or(defaults.min_duration),
+        min_gap: min_gap.unwrap_or(defaults.min_gap),
+    }))
+}
+
+/// `<input>.<tag>.<ext>`, the default output of the commands rewriting a
+/// subtitle file.
+fn tagged_output(input_path: &str, tag: &str) -> String {
+    let path = Path::new(input_path);
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("srt");
+    path.with_extension(format!("{}.{}", tag, ext))
+        .to_string_lossy()
+        .to_string()
+}
+
+/// Removes the `retime` options; exactly one of `--shift`, `--fps` or two
+/// `--sync` points must be given.
+fn take_retime(args: &mut Vec<String>) -> Result<Retime, String> {
+    let time = |s: &str| parse_time(s).ok_or_else(|| format!("Invalid time '{}'", s));
+    let shift = take_value(args, "--shift")?;
+    let fps = take_value(args, "--fps")?;
+    let sync = take_values(args, "--sync")?;
+
+    match (shift, fps, sync.as_slice()) {
+        (Some(shift), None, []) => Ok(Retime::Shift(time(&shift)?)),
+        (None, Some(fps), []) => {
+            let rate = |s: &str| {
+                s.trim()
+                    .parse::<f64>()
+                    .map_err(|_| format!("Invalid frame rate '{}'", s))
+            };
+            let (from, to) = fps.split_once(':').ok_or_else(|| {
+                format!("Invalid --fps '{}'. Use <from>:<to>, e.g. 23.976:25", fps)
+            })?;
+            Ok(Retime::Scale {
+                from_fps: rate(from)?,
+                to_fps: rate(to)?,
+            })
+        }
+        (None, None, [first, second]) => {
+            let point = |s: &String| -> Result<SyncPoint, String> {
+                let (cue, at) = s.split_once('=').ok_or_else(|| {
+                    format!("Invalid --sync '{}'. Use <cue>=<time>, e.g. 10=00:01:05", s)
+                })?;
+                let cue = cue
+                    .trim()
+                    .parse::<usize>()
+                    .map_err(|_| format!("Invalid cue number '{}'", cue))?;
+                Ok(SyncPoint {
+                    cue,
+                    time: time(at)?,
+                })
+            };
+            Ok(Retime::Sync(point(first)?, point(second)?))
+        }
+        _ => Err(
+            "Give one of --shift <time>, --fps <from>:<to>, or two --sync <cue>=<time>".to_string(),
+        ),
+    }
+}
+
+/// Removes the output options of the subtitle commands: `--format`,
+/// `--cue-settings`, `--merge` with its limits `--max-chars` and
+/// `--max-duration`, and `--fix-timing` with its limits `--min-duration` and
+/// `--min-gap` (any limit turns its pass on).
+fn take_subtitle_options(args: &mut Vec<String>) -> Result<DownloadOptions, String> {
+    let format = take_value(args, "--format")?
+        .map(|f| f.parse::<SubtitleFormat>().map_err(|e| e.to_string()))
+        .transpose()?;
+    let cue_settings = take_value(args, "--cue-settings")?;
+
+    let merge_flag = take_flag(args, "--merge");
+    let max_chars = take_value(args, "--max-chars")?
+        .map(|s| {
+            s.parse::<usize>()
+                .map_err(|_| format!("Invalid --max-chars '{}'", s))
+        })
+        .transpose()?;
+    let max_duration = take_value(args, "--max-duration")?
+        .map(|s| {
+            s.parse::<f64>()
+                .map_err(|_| format!("Invalid --max-duration '{}'", s))
+        })
+        .transpose()?;
+    let merge = (merge_flag || max_chars.is_some() || max_duration.is_some()).then(|| {
+        let defaults = MergeOptions::default();
+        MergeOptions {
+            max_chars: max_chars.unwrap_or(defaults.max_chars),
+            max_duration: max_duration.unwrap_or(defaults.max_duration),
+        }
+    });
+
+    let fix_timing = take_flag(args, "--fix-timing");
+    let timing = match take_timing_options(args)? {
+        Some(timing) => Some(timing),
+        None => fix_timing.then(TimingOptions::default),
+    };
+
+    Ok(DownloadOptions {
+        format,
+        cue_settings,
+        merge,
+        timing,
+        ..DownloadOptions::default()
+    })
+}
+
+/// Removes the `--header <n>` option, returning the number of header lines.
+fn take_header(args: &mut Vec<String>) -> Result<Option<usize>, String> {
+    take_value(args, "--header")?
+        .map(|s| {
+            s.parse::<usize>()
+                .map_err(|_| "Invalid header line count".to_string())
+        })
+        .transpose()
+}
+
+/// Removes the unit flags (`--cues`, `--csv`) and `--header <n>` shared by
+/// the chunking commands. CSV files get a one-line header by default.
+fn take_unit_options(args: &mut Vec<String>) -> Result<(SplitUnit, usize), String> {
+    let cues = take_flag(args, "--cues");
+    let csv = take_flag(args, "--csv");
+    let header = take_header(args)?;
+    match (cues, csv) {
+        (true, true) => Err("Use either --cues or --csv, not both".to_string()),
+        (true, false) => Ok((SplitUnit::Cues, header.unwrap_or(0))),
+        (false, true) => Ok((SplitUnit::CsvRecords, header.unwrap_or(1))),
+        (false, false) => Ok((SplitUnit::Lines, header.unwrap_or(0))),
+    }
+}
+
+/// Redraws a single progress line on stderr while a split runs.
+fn print_progress(progress: &SplitProgress) {
+    let part = progress
+        .current_part
+        .map(|p| format!(" | Part {}", p))
+        .unwrap_or_default();
+    eprint!(
+        "\r⏳ {:>3.0}% | {} lines{}",
+        progress.fraction() * 100.0,
+        progress.lines_read,
+        part
+    );
+    if progress.bytes_read >= progress.total_bytes {
+        eprintln!();
+    }
+}
+
+/// Removes the output options every split command accepts.
+fn take_split_flags(args: &mut Vec<String>) -> Result<Option<SplitFlags>, String> {
+    let parallel = take_flag(args, "--parallel");
+    let line_prefix = match take_value(args, "--prefix")?.as_deref() {
+        None => None,
+        Some("lines") => Some(LinePrefix::LineNumber),
+        Some("time") => Some(LinePrefix::CueStart),
+        Some(other) => {
+            return Err(format!(
+                "Unknown prefix '{}'. Use 'lines' or 'time'.",
+                other
+            ));
+        }
+    };
+    let transforms = take_transforms(args)?;
+    let naming = take_value(args, "--name")?.map(PartNaming::Template);
+    let overlap = take_value(args, "--overlap")?
+        .map(|s| s.parse::<usize>().map_err(|_| "Invalid overlap number"))
+        .transpose()?
+        .unwrap_or(0);
+    if !parallel
+        && line_prefix.is_none()
+        && transforms.is_empty()
+        && naming.is_none()
+        && overlap == 0
+    {
+        return Ok(None);
+    }
+    Ok(Some(SplitFlags {
+        parallel,
+        line_prefix,
+        transforms,
+        naming,
+        overlap,
+    }))
+}
+
+/// Removes every `flag <value>` occurrence, returning the values in order.
+fn take_values(args: &mut Vec<String>, flag: &str) -> Result<Vec<String>, String> {
+    let mut values = Vec::new();
+    while let Some(value) = take_value(args, flag)? {
+        values.push(value);
+    }
+    Ok(values)
+}
+
+/// Removes the line filter flags. Repeated `--include` / `--exclude`
+/// patterns are alternatives, as with `grep -e`. Filters run first, then
+/// trimming, then blank-line collapsing.
+fn take_transforms(args: &mut Vec<String>) -> Result<Vec<SharedTransform>, String> {
+    let any_of = |patterns: Vec<String>| -> Result<Option<String>, String> {
+        for pattern in &patterns {
+            // Checked one by one so an error names the pattern as typed
+            RegexFilter::include(pattern)?;
+        }
+        Ok((!patterns.is_empty()).then(|| {
+            patterns
+                .iter()
+                .map(|p| format!("(?:{})", p))
+                .collect::<Vec<_>>()
+                .join("|")
+        }))
+    };
+    let include = any_of(take_values(args, "--include")?)?;
+    let exclude = any_of(take_values(args, "--exclude")?)?;
+
+    let mut transforms: Vec<SharedTransform> = Vec::new();
+    if let Some(pattern) = include {
+        transforms.push(Arc::new(Mutex::new(RegexFilter::include(&pattern)?)));
+    }
+    if let Some(pattern) = exclude {
+        transforms.push(Arc::new(Mutex::new(RegexFilter::exclude(&pattern)?)));
+    }
+    if take_flag(args, "--trim") {
+        transforms.push(Arc::new(Mutex::new(TrimWhitespace)));
+    }
+    if take_flag(args, "--collapse-blank") {
+        transforms.push(Arc::new(Mutex::new(CollapseBlankLines::default())));
+    }
+    Ok(transforms)
+}
+
 fn parse_args(args: &[String]) -> Result<AppMode, String> {
+    let mut args = args.to_vec();
+    let flags = take_split_flags(&mut args)?;
+    match (parse_command(&args)?, flags) {
+        (AppMode::Split { planner, .. }, Some(flags)) => Ok(AppMode::Split { planner, flags }),
+        // JSON and text are split while streaming, not from a plan, so only
+        // the part names carry over
+        (AppMode::JsonSplit { input_path, limit, .. }, Some(flags)) if flags.names_only() => {
+            Ok(AppMode::JsonSplit {
+                input_path,
+                limit,
+                naming: flags.naming.unwrap_or_default(),
+            })
+        }
+        (AppMode::TextSplit { input_path, size, .. }, Some(flags)) if flags.names_only() => {
+            Ok(AppMode::TextSplit {
+                input_path,
+                size,
+                naming: flags.naming.unwrap_or_default(),
+            })
+        }
+        (AppMode::JsonSplit { .. } | AppMode::TextSplit { .. }, Some(_)) => Err(
+            "json and text only take --name; --parallel, --prefix, --overlap and filters apply to the line-based split commands"
+                .to_string(),
+        ),
+        (_, Some(_)) => Err(
+            "Output options (--parallel, --prefix, --name, --overlap, filters) only apply to the split commands"
+                .to_string(),
+        ),
+        (mode, None) => Ok(mode),
+    }
+}
+
+fn parse_command(args: &[String]) -> Result<AppMode, String> {
     if args.len() < 2 {
         return Ok(AppMode::Ui);
     }
@@ -62,56 +522,246 @@ fn parse_args(args: &[String]) -> Result<AppMode, String> {
             })
         }
         "download" => {
-            if args.len() < 3 {
-                return Err("Usage: download <video_id_or_url> [lang]".to_string());
+            let mut rest = args[2..].to_vec();
+            let output_path = take_value(&mut rest, "--output")?;
+            let mut options = take_subtitle_options(&mut rest)?;
+            if rest.is_empty() || rest.len() > 3 {
+                return Err(
+                    "Usage: download <video_id_or_url> [lang] [second_lang] [--output <file>] [--format <format>] [--cue-settings <settings>] [--merge] [--fix-timing]"
+                        .to_string(),
+                );
             }
-            let video_id = args[2].clone();
-            let lang = if args.len() > 3 {
-                args[3].clone()
-            } else {
-                "en".to_string()
-            };
-            Ok(AppMode::Download { video_id, lang })
+            let video_id = rest[0].clone();
+            let lang = rest.get(1).cloned().unwrap_or_else(|| "en".to_string());
+            options.secondary_language = rest.get(2).cloned();
+            Ok(AppMode::Download {
+                video_id,
+                lang,
+                output_path,
+                options,
+            })
+        }
+        "convert" => {
+            let mut rest = args[2..].to_vec();
+            let options = take_subtitle_options(&mut rest)?;
+            if rest.len() != 2 {
+                return Err(
+                    "Usage: convert <input> <output> [--format <format>] [--cue-settings <settings>] [--merge] [--fix-timing]"
+                        .to_string(),
+                );
+            }
+            Ok(AppMode::Convert {
+                input_path: rest[0].clone(),
+                output_path: rest[1].clone(),
+                options,
+            })
+        }
+        "fix" => {
+            let mut rest = args[2..].to_vec();
+            let options = take_timing_options(&mut rest)?.unwrap_or_default();
+            if rest.is_empty() || rest.len() > 2 {
+                return Err(
+                    "Usage: fix <input> [output] [--min-duration <seconds>] [--min-gap <seconds>]"
+                        .to_string(),
+                );
+            }
+            let input_path = rest[0].clone();
+            let output_path = rest
+                .get(1)
+                .cloned()
+                .unwrap_or_else(|| tagged_output(&input_path, "fixed"));
+            Ok(AppMode::Fix {
+                input_path,
+                output_path,
+                options,
+            })
+        }
+        "retime" => {
+            let mut rest = args[2..].to_vec();
+            let retime = take_retime(&mut rest)?;
+            if rest.is_empty() || rest.len() > 2 {
+                return Err(
+                    "Usage: retime <input> [output] (--shift <time> | --fps <from>:<to> | --sync <cue>=<time> --sync <cue>=<time>)"
+                        .to_string(),
+                );
+            }
+            let input_path = rest[0].clone();
+            let output_path = rest
+                .get(1)
+                .cloned()
+                .unwrap_or_else(|| tagged_output(&input_path, "retimed"));
+            Ok(AppMode::Retime {
+                input_path,
+                output_path,
+                retime,
+            })
         }
         "nth" => {
-            if args.len() < 4 {
-                return Err("Usage: nth <file> <size>".to_string());
+            let mut rest = args[2..].to_vec();
+            let (unit, header_lines) = take_unit_options(&mut rest)?;
+            if rest.len() != 2 {
+                return Err("Usage: nth <file> <size> [--cues | --csv] [--header <n>]".to_string());
             }
-            let input_path = args[2].clone();
-            let size = args[3]
+            let input_path = rest[0].clone();
+            let size = rest[1]
                 .parse::<usize>()
                 .map_err(|_| "Invalid chunk size number")?;
-            println!("🔄 'nth' (Auto) Mode selected ({} lines/chunk)", size);
+            let unit_name = match unit {
+                SplitUnit::Lines => "lines",
+                SplitUnit::Cues => "cues",
+                SplitUnit::CsvRecords => "records",
+            };
+            println!(
+                "🔄 'nth' (Auto) Mode selected ({} {}/chunk)",
+                size, unit_name
+            );
+            let mode = SplitMode::Auto {
+                chunk_size: size,
+                unit,
+            };
             Ok(AppMode::Split {
-                input_path,
-                mode: SplitMode::Auto {
-                    chunk_size: size,
-                    output_dir: None,
-                },
+                planner: SplitPlanner::new(input_path, mode).with_header_lines(header_lines),
+                flags: SplitFlags::default(),
             })
         }
         "manual" => {
             if args.len() < 4 {
-                return Err("Usage: manual <file> <range>...".to_string());
+                return Err("Usage: manual <file> [--strict] <range>...".to_string());
             }
             let input_path = args[2].clone();
-            let ranges = args[3..].to_vec();
+            let mut ranges = args[3..].to_vec();
+            let strict = take_flag(&mut ranges, "--strict");
+            let header_lines = take_header(&mut ranges)?.unwrap_or(0);
+            if ranges.is_empty() {
+                return Err("Usage: manual <file> [--strict] <range>...".to_string());
+            }
+            let ranges = ranges
+                .iter()
+                .map(|range| parse_range(range))
+                .collect::<Result<Vec<_>, _>>()?;
             println!("🔧 'manual' Mode selected");
+            let mode = SplitMode::Manual { ranges, strict };
             Ok(AppMode::Split {
-                input_path,
-                mode: SplitMode::Manual {
-                    ranges,
-                    output_dir: None,
-                },
+                planner: SplitPlanner::new(input_path, mode).with_header_lines(header_lines),
+                flags: SplitFlags::default(),
+            })
+        }
+        "ratio" => {
+            let mut rest = args[2..].to_vec();
+            let (unit, header_lines) = take_unit_options(&mut rest)?;
+            let seed = take_value(&mut rest, "--seed")?
+                .map(|s| s.parse::<u64>().map_err(|_| "Invalid seed number"))
+                .transpose()?;
+            if rest.len() != 2 {
+                return Err(
+                    "Usage: ratio <file> <shares> [--cues | --csv] [--header <n>] [--seed <n>]"
+                        .to_string(),
+                );
+            }
+            let shares = parse_shares(&rest[1])?;
+            println!("⚖️  'ratio' Mode selected ({})", rest[1]);
+            let mode = SplitMode::Proportional { shares, unit, seed };
+            Ok(AppMode::Split {
+                planner: SplitPlanner::new(&rest[0], mode).with_header_lines(header_lines),
+                flags: SplitFlags::default(),
+            })
+        }
+        "interleave" => {
+            let mut rest = args[2..].to_vec();
+            let (unit, header_lines) = take_unit_options(&mut rest)?;
+            if rest.len() != 2 {
+                return Err(
+                    "Usage: interleave <file> <parts> [--cues | --csv] [--header <n>]".to_string(),
+                );
+            }
+            let parts = rest[1]
+                .parse::<usize>()
+                .map_err(|_| "Invalid number of parts")?;
+            println!("🔀 'interleave' Mode selected ({} parts)", parts);
+            let mode = SplitMode::RoundRobin { parts, unit };
+            Ok(AppMode::Split {
+                planner: SplitPlanner::new(&rest[0], mode).with_header_lines(header_lines),
+                flags: SplitFlags::default(),
+            })
+        }
+        "markdown" => {
+            let mut rest = args[2..].to_vec();
+            let repeat_parents = take_flag(&mut rest, "--parents");
+            if rest.is_empty() || rest.len() > 2 {
+                return Err("Usage: markdown <file> [level] [--parents]".to_string());
+            }
+            let level = match rest.get(1) {
+                Some(level) => level
+                    .parse::<usize>()
+                    .map_err(|_| "Invalid heading level number")?,
+                None => 2,
+            };
+            println!("📑 'markdown' Mode selected (level {} headings)", level);
+            let mode = SplitMode::Markdown {
+                level,
+                repeat_parents,
+            };
+            Ok(AppMode::Split {
+                planner: SplitPlanner::new(&rest[0], mode),
+                flags: SplitFlags::default(),
+            })
+        }
+        "json" => {
+            if args.len() != 4 {
+                return Err("Usage: json <file> <records | size>".to_string());
+            }
+            let limit = parse_json_limit(&args[3])?;
+            println!("🧩 'json' Mode selected ({})", args[3]);
+            Ok(AppMode::JsonSplit {
+                input_path: args[2].clone(),
+                limit,
+                naming: PartNaming::default(),
+            })
+        }
+        "text" => {
+            if args.len() != 4 {
+                return Err("Usage: text <file> <lines | <n>c | <n>t>".to_string());
+            }
+            let size = parse_text_size(&args[3])?;
+            println!("📝 'text' Mode selected ({})", args[3]);
+            Ok(AppMode::TextSplit {
+                input_path: args[2].clone(),
+                size,
+                naming: PartNaming::default(),
+            })
+        }
+        "index" => {
+            if args.len() != 3 {
+                return Err("Usage: index <file>".to_string());
+            }
+            Ok(AppMode::Index {
+                input_path: args[2].clone(),
             })
         }
         _ => Err(format!(
-            "Unknown command: '{}'. Use 'nth', 'manual', 'scan', 'download', or run without args for UI.",
+            "Unknown command: '{}'. Use 'nth', 'manual', 'ratio', 'interleave', 'markdown', 'json', 'text', 'index', 'scan', 'download', 'convert', 'fix', 'retime', or run without args for UI.",
             command
         )),
     }
 }
 
+/// A token cancelled by the first Ctrl-C, so the split stops and removes
+/// the parts it wrote. A second Ctrl-C exits at once.
+fn cancel_on_ctrl_c() -> CancelToken {
+    let cancel = CancelToken::new();
+    let token = cancel.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            eprintln!("\n⏹️  Cancelling, removing the parts written so far...");
+            token.cancel();
+        }
+        if tokio::signal::ctrl_c().await.is_ok() {
+            process::exit(130);
+        }
+    });
+    cancel
+}
+
 #[tokio::main]
 async fn run() -> Result<(), Box<dyn std::error::Error>> {
     let args: Vec<String> = env::args().collect();
@@ -144,22 +794,115 @@ async fn run() -> Result<(), Box<dyn std::error::Error>> {
             }
             Ok(())
         }
-        AppMode::Download { video_id, lang } => {
+        AppMode::Download {
+            video_id,
+            lang,
+            output_path,
+            options,
+        } => {
             let id = extract_id(&video_id);
-            println!("⬇️  Downloading subtitle for ID: {} (Lang: {})", id, lang);
+            match &options.secondary_language {
+                Some(second) => println!(
+                    "⬇️  Downloading subtitle for ID: {} (Lang: {} + {})",
+                    id, lang, second
+                ),
+                None => println!("⬇️  Downloading subtitle for ID: {} (Lang: {})", id, lang),
+            }
 
-            let filename = download_subtitle(&video_id, Some(lang), None).await?;
+            let filename =
+                download_subtitle_with_options(&video_id, Some(lang), output_path, options).await?;
 
             println!("✅ Successfully saved subtitle to: {}", filename);
             Ok(())
         }
-        AppMode::Split { input_path, mode } => {
-            let configs = build_split_plan(input_path.clone(), mode).map_err(|e| e.to_string())?;
-            println!("✅ Plan created: {} parts.", configs.len());
-            let success_msg = split_file(&input_path, &configs).map_err(|e| e.to_string())?;
+        AppMode::Convert {
+            input_path,
+            output_path,
+            options,
+        } => {
+            println!("🔄 Converting {} to {}...", input_path, output_path);
+            let cues = convert_subtitle(&input_path, &output_path, options)?;
+            println!("✅ Wrote {} cues to {}", cues, output_path);
+            Ok(())
+        }
+        AppMode::Fix {
+            input_path,
+            output_path,
+            options,
+        } => {
+            println!("🔧 Fixing cue timing in {}...", input_path);
+            let report = fix_subtitle(&input_path, &output_path, options)?;
+            println!(
+                "✅ Wrote {} cues to {} ({} adjusted, {} merged, {} empty dropped)",
+                report.cues, output_path, report.adjusted, report.merged, report.dropped
+            );
+            Ok(())
+        }
+        AppMode::Retime {
+            input_path,
+            output_path,
+            retime,
+        } => {
+            println!("⏱️  Retiming {}...", input_path);
+            let cues = retime_subtitle(&input_path, &output_path, retime)?;
+            println!("✅ Wrote {} cues to {}", cues, output_path);
+            Ok(())
+        }
+        AppMode::Split { planner, flags } => {
+            let input_path = planner.input_path().to_path_buf();
+            let mut planner = planner.with_overlap(flags.overlap);
+            if let Some(naming) = flags.naming {
+                planner = planner.with_naming(naming);
+            }
+            let plan = planner.build_async().await?;
+            for warning in &plan.warnings {
+                println!("⚠️  {}", warning);
+            }
+            if let Some(coverage) = &plan.coverage {
+                println!("📊 {}", coverage);
+            }
+            println!("✅ Plan created: {} parts.", plan.configs.len());
+            let mut options = plan.options;
+            options.progress = Some(Arc::new(print_progress));
+            options.parallel = flags.parallel;
+            options.line_prefix = flags.line_prefix;
+            options.transforms = flags.transforms;
+            options.cancel = Some(cancel_on_ctrl_c());
+            let success_msg = split_file_async(input_path, plan.configs, options)
+                .await
+                .map_err(|e| e.to_string())?;
+            println!("✅ {}", success_msg);
+            Ok(())
+        }
+        AppMode::JsonSplit {
+            input_path,
+            limit,
+            naming,
+        } => {
+            let success_msg = run_json_split(input_path, limit, None, naming)?;
             println!("✅ {}", success_msg);
             Ok(())
         }
+        AppMode::TextSplit {
+            input_path,
+            size,
+            naming,
+        } => {
+            let success_msg = run_text_split(input_path, size, None, naming)?;
+            println!("✅ {}", success_msg);
+            Ok(())
+        }
+        AppMode::Index { input_path } => {
+            println!("🗂️  Indexing {}...", input_path);
+            let index = LineIndex::build(&input_path)?;
+            let sidecar = index.save(&input_path)?;
+            println!(
+                "✅ Indexed {} lines into {}",
+                index.total_lines(),
+                sidecar.display()
+            );
+            Ok(())
+        }
     }
 }
 
@@ -169,3 +912,93 @@ fn main() {
         process::exit(1);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn retime(args: &[&str]) -> Result<Retime, String> {
+        let mut args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        take_retime(&mut args)
+    }
+
+    #[test]
+    fn retime_options() {
+        assert_eq!(
+            retime(&["--shift", "-00:00:02,500"]),
+            Ok(Retime::Shift(-2.5))
+        );
+        assert_eq!(
+            retime(&["--fps", "24:25"]),
+            Ok(Retime::Scale {
+                from_fps: 24.0,
+                to_fps: 25.0
+            })
+        );
+        assert_eq!(
+            retime(&["--sync", "1=0", "--sync", "10=01:05"]),
+            Ok(Retime::Sync(
+                SyncPoint { cue: 1, time: 0.0 },
+                SyncPoint {
+                    cue: 10,
+                    time: 65.0
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn retime_needs_exactly_one_mode() {
+        for args in [
+            &[][..],
+            &["--sync", "1=0"],
+            &["--sync", "1=0", "--sync", "2=1", "--sync", "3=2"],
+            &["--shift", "1", "--fps", "24:25"],
+        ] {
+            assert!(
+                retime(args).unwrap_err().starts_with("Give one of"),
+                "{args:?}"
+            );
+        }
+        assert!(retime(&["--sync", "x=0", "--sync", "2=1"]).is_err());
+        assert!(retime(&["--fps", "25"]).is_err());
+    }
+
+    fn parse(args: &[&str]) -> Result<AppMode, String> {
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        parse_args(&args)
+    }
+
+    #[test]
+    fn json_and_text_take_part_names() {
+        let template = PartNaming::Template("{stem}_{n}.{ext}".to_string());
+        assert!(matches!(
+            parse(&["app", "json", "a.json", "100", "--name", "{stem}_{n}.{ext}"]),
+            Ok(AppMode::JsonSplit { naming, .. }) if naming == template
+        ));
+        assert!(matches!(
+            parse(&["app", "text", "a.txt", "200"]),
+            Ok(AppMode::TextSplit {
+                naming: PartNaming::Numbered,
+                ..
+            })
+        ));
+        for args in [
+            &["app", "text", "a.txt", "200", "--overlap", "2"][..],
+            &[
+                "app",
+                "json",
+                "a.json",
+                "100",
+                "--name",
+                "{n}",
+                "--parallel",
+            ],
+        ] {
+            assert!(
+                parse(args).is_err_and(|e| e.starts_with("json and text only take --name")),
+                "{args:?}"
+            );
+        }
+    }
+}
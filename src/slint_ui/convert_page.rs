@@ -0,0 +1,130 @@
+use super::AppWindow;
+use slint::ComponentHandle;
+use std::path::Path;
+use youtube_subtitle_manager::{
+    DownloadOptions, READABLE_FORMATS, SubtitleFormat, convert_subtitle,
+};
+
+pub fn setup_handlers(ui: &AppWindow) {
+    setup_input_picker_handler(ui);
+    setup_output_picker_handler(ui);
+    setup_execute_convert_handler(ui);
+}
+
+/// Suggested output for `input`: WebVTT for an SRT file, SRT otherwise.
+fn default_output(input: &str) -> String {
+    let format = match SubtitleFormat::from_path(input) {
+        Some(SubtitleFormat::Srt) => SubtitleFormat::WebVtt,
+        _ => SubtitleFormat::Srt,
+    };
+    Path::new(input)
+        .with_extension(format.extension())
+        .to_string_lossy()
+        .to_string()
+}
+
+fn setup_input_picker_handler(ui: &AppWindow) {
+    let ui_handle = ui.as_weak();
+    ui.on_pick_convert_input(move || {
+        let ui_handle = ui_handle.clone();
+        let extensions: Vec<&str> = READABLE_FORMATS.iter().map(|f| f.extension()).collect();
+        tokio::spawn(async move {
+            if let Some(file) = rfd::AsyncFileDialog::new()
+                .set_title("Select Subtitle File")
+                .add_filter("Subtitles", &extensions)
+                .pick_file()
+                .await
+            {
+                let path = file.path().to_string_lossy().to_string();
+                let _ = slint::invoke_from_event_loop(move || {
+                    if let Some(ui) = ui_handle.upgrade() {
+                        if ui.get_convert_output_path().is_empty() {
+                            ui.set_convert_output_path(default_output(&path).into());
+                        }
+                        ui.set_convert_input_path(path.into());
+                    }
+                });
+            }
+        });
+    });
+}
+
+fn setup_output_picker_handler(ui: &AppWindow) {
+    let ui_handle = ui.as_weak();
+    ui.on_pick_convert_output(move || {
+        let ui_handle = ui_handle.clone();
+        let current = ui_handle
+            .upgrade()
+            .map(|ui| ui.get_convert_output_path().to_string())
+            .unwrap_or_default();
+        tokio::spawn(async move {
+            let mut dialog = rfd::AsyncFileDialog::new().set_title("Save Converted Subtitle");
+            // The current output's format comes first so the dialog defaults to it
+            let current_format = SubtitleFormat::from_path(&current);
+            let formats = current_format.iter().chain(
+                SubtitleFormat::ALL
+                    .iter()
+                    .filter(|f| Some(**f) != current_format),
+            );
+            for format in formats {
+                dialog = dialog.add_filter(format.label(), &[format.extension()]);
+            }
+            if let Some(name) = Path::new(&current).file_name() {
+                dialog = dialog.set_file_name(name.to_string_lossy());
+            }
+
+            if let Some(file) = dialog.save_file().await {
+                let path = file.path().to_string_lossy().to_string();
+                let _ = slint::invoke_from_event_loop(move || {
+                    if let Some(ui) = ui_handle.upgrade() {
+                        ui.set_convert_output_path(path.into());
+                    }
+                });
+            }
+        });
+    });
+}
+
+fn setup_execute_convert_handler(ui: &AppWindow) {
+    let ui_handle = ui.as_weak();
+    ui.on_execute_convert(move |input_path, output_path| {
+        let input_path = input_path.to_string();
+        let output_path = output_path.to_string();
+        let ui_handle = ui_handle.clone();
+        if let Some(ui) = ui_handle.upgrade() {
+            ui.set_convert_running(true);
+            ui.set_convert_status_message("Converting...".into());
+            ui.set_convert_status_color(slint::Color::from_rgb_u8(100, 100, 100));
+        }
+
+        tokio::spawn(async move {
+            // Reading, parsing and writing are blocking file work
+            let result = tokio::task::spawn_blocking({
+                let output_path = output_path.clone();
+                move || convert_subtitle(&input_path, &output_path, DownloadOptions::default())
+            })
+            .await
+            .map_err(|e| format!("Convert task failed: {}", e))
+            .and_then(|result| result.map_err(|e| e.to_string()));
+
+            // Update UI
+            let _ = slint::invoke_from_event_loop(move || {
+                if let Some(ui) = ui_handle.upgrade() {
+                    ui.set_convert_running(false);
+                    match result {
+                        Ok(cues) => {
+                            ui.set_convert_status_message(
+                                format!("Converted {} cues to {}", cues, output_path).into(),
+                            );
+                            ui.set_convert_status_color(slint::Color::from_rgb_u8(0, 150, 0));
+                        }
+                        Err(e) => {
+                            ui.set_convert_status_message(format!("Error: {}", e).into());
+                            ui.set_convert_status_color(slint::Color::from_rgb_u8(255, 0, 0));
+                        }
+                    }
+                }
+            });
+        });
+    });
+}
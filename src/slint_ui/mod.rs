@@ -1,5 +1,6 @@
 slint::include_modules!();
 
+mod convert_page;
 mod split_page;
 mod youtube_page;
 
@@ -8,6 +9,7 @@ pub fn run_ui() -> Result<(), Box<dyn std::error::Error>> {
 
     youtube_page::setup_handlers(&ui);
     split_page::setup_handlers(&ui);
+    convert_page::setup_handlers(&ui);
 
     ui.run()?;
     Ok(())
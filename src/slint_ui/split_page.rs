@@ -1,12 +1,23 @@
 use super::AppWindow;
-use crate::tasks::{self, SplitMode};
-use file_spliter::split_file;
+use file_spliter::{
+    CancelToken, ProgressCallback, SplitMode, SplitPlan, SplitPlanner, SplitProgress, SplitUnit,
+    parse_range, split_file_async,
+};
 use slint::ComponentHandle;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Cancel token of the split currently running, shared between the
+/// "Split File" and "Cancel" buttons.
+type ActiveSplit = Arc<Mutex<Option<CancelToken>>>;
 
 pub fn setup_handlers(ui: &AppWindow) {
+    let active_split = ActiveSplit::default();
+
     setup_file_picker_handler(ui);
     setup_folder_picker_handler(ui);
-    setup_execute_split_handler(ui);
+    setup_execute_split_handler(ui, active_split.clone());
+    setup_cancel_split_handler(ui, active_split);
 }
 
 fn setup_file_picker_handler(ui: &AppWindow) {
@@ -52,43 +63,110 @@ fn setup_folder_picker_handler(ui: &AppWindow) {
 }
 
 /// Executes the split logic based on the provided mode and parameters.
+/// Returns the summary to show once the split is done.
 async fn process_split_task(
     input_path: String,
     output_path: Option<String>,
     mode_index: i32,
     param: String,
-) -> Result<String, String> {
+    strict: bool,
+    progress: ProgressCallback,
+    cancel: CancelToken,
+) -> Result<(String, bool), String> {
     let mode = if mode_index == 0 {
         let size = param
             .parse::<usize>()
             .map_err(|_| "Invalid chunk size: must be a positive number")?;
         SplitMode::Auto {
             chunk_size: size,
-            output_dir: output_path,
+            unit: SplitUnit::Lines,
         }
     } else {
-        let ranges: Vec<String> = param.split_whitespace().map(|s| s.to_string()).collect();
+        let ranges = param
+            .split_whitespace()
+            .map(parse_range)
+            .collect::<Result<Vec<_>, _>>()?;
         if ranges.is_empty() {
             return Err("No ranges provided".into());
         }
-        SplitMode::Manual {
-            ranges,
-            output_dir: output_path,
-        }
+        SplitMode::Manual { ranges, strict }
     };
 
     // 1. Build Plan
-    let configs = tasks::build_split_plan(input_path.clone(), mode)?;
+    let mut planner = SplitPlanner::new(&input_path, mode);
+    if let Some(dir) = output_path {
+        planner = planner.with_output_dir(dir);
+    }
+    let plan = planner.build_async().await?;
+    // Planning reads the whole input too; a cancel during it stops here
+    if cancel.is_cancelled() {
+        return Err("Split cancelled.".into());
+    }
+    let summary = format_split_summary(&plan);
+    let has_warnings = !plan.warnings.is_empty();
 
     // 2. Execute Split
-    split_file(&input_path, &configs)?;
+    let mut options = plan.options;
+    options.progress = Some(progress);
+    options.cancel = Some(cancel);
+    split_file_async(input_path, plan.configs, options).await?;
+
+    Ok((summary, has_warnings))
+}
+
+/// Builds the status line shown after a successful split, including any
+/// warnings raised while planning.
+fn format_split_summary(plan: &SplitPlan) -> String {
+    let mut lines = vec![format!(
+        "Successfully split into {} parts.",
+        plan.configs.len()
+    )];
+    if let Some(coverage) = &plan.coverage {
+        lines.push(coverage.clone());
+    }
+    for warning in &plan.warnings {
+        lines.push(format!("Warning: {}", warning));
+    }
+    lines.join("\n")
+}
+
+/// Forwards progress to the progress bar, only when the whole percentage
+/// changes so the event loop is not flooded.
+fn progress_reporter(ui_handle: slint::Weak<AppWindow>) -> ProgressCallback {
+    let last_percent = AtomicU32::new(u32::MAX);
+    Arc::new(move |progress: &SplitProgress| {
+        let fraction = progress.fraction();
+        let percent = (fraction * 100.0) as u32;
+        if last_percent.swap(percent, Ordering::Relaxed) == percent {
+            return;
+        }
+
+        let ui_handle = ui_handle.clone();
+        let _ = slint::invoke_from_event_loop(move || {
+            if let Some(ui) = ui_handle.upgrade() {
+                ui.set_split_progress(fraction);
+            }
+        });
+    })
+}
 
-    Ok(format!("Successfully split into {} parts.", configs.len()))
+fn setup_cancel_split_handler(ui: &AppWindow, active_split: ActiveSplit) {
+    let ui_handle = ui.as_weak();
+    ui.on_cancel_split(move || {
+        let Some(token) = active_split.lock().unwrap().clone() else {
+            return;
+        };
+        token.cancel();
+        if let Some(ui) = ui_handle.upgrade() {
+            ui.set_split_status_message("Cancelling...".into());
+            ui.set_split_status_color(slint::Color::from_rgb_u8(200, 120, 0));
+        }
+    });
 }
 
-fn setup_execute_split_handler(ui: &AppWindow) {
+fn setup_execute_split_handler(ui: &AppWindow, active_split: ActiveSplit) {
     let ui_handle = ui.as_weak();
-    ui.on_execute_split(move |input_path, output_path, mode_index, param| {
+    ui.on_execute_split(move |input_path, output_path, mode_index, param, strict| {
         let input_path = input_path.to_string();
         let output_path = if output_path.is_empty() {
             None
@@ -98,17 +176,43 @@ fn setup_execute_split_handler(ui: &AppWindow) {
         let param = param.to_string();
         let ui_handle = ui_handle.clone();
 
+        let cancel = CancelToken::new();
+        *active_split.lock().unwrap() = Some(cancel.clone());
+        let active_split = active_split.clone();
+        let progress = progress_reporter(ui_handle.clone());
+        if let Some(ui) = ui_handle.upgrade() {
+            ui.set_split_running(true);
+            ui.set_split_progress(0.0);
+            ui.set_split_status_message("Splitting...".into());
+            ui.set_split_status_color(slint::Color::from_rgb_u8(100, 100, 100));
+        }
+
         tokio::spawn(async move {
             // Determine logic based on mode_index (0 = Auto, 1 = Manual)
-            let result = process_split_task(input_path, output_path, mode_index, param).await;
+            let result = process_split_task(
+                input_path,
+                output_path,
+                mode_index,
+                param,
+                strict,
+                progress,
+                cancel,
+            )
+            .await;
+            *active_split.lock().unwrap() = None;
 
             // Update UI
             let _ = slint::invoke_from_event_loop(move || {
                 if let Some(ui) = ui_handle.upgrade() {
+                    ui.set_split_running(false);
                     match result {
-                        Ok(msg) => {
-                            ui.set_split_status_message(msg.into());
-                            ui.set_split_status_color(slint::Color::from_rgb_u8(0, 150, 0)); // Greenish
+                        Ok((summary, has_warnings)) => {
+                            ui.set_split_status_message(summary.into());
+                            if !has_warnings {
+                                ui.set_split_status_color(slint::Color::from_rgb_u8(0, 150, 0)); // Greenish
+                            } else {
+                                ui.set_split_status_color(slint::Color::from_rgb_u8(200, 120, 0)); // Orange
+                            }
                         }
                         Err(e) => {
                             ui.set_split_status_message(format!("Error: {}", e).into());
@@ -1,16 +1,33 @@
 use super::{AppWindow, SubtitleItem};
 use slint::{ComponentHandle, Image, SharedPixelBuffer, SharedString, VecModel};
+use std::path::Path;
 use std::rc::Rc;
 use youtube_subtitle_manager::{
-    download_subtitle, extract_id, fetch_video_details, scan_subtitles,
+    DownloadOptions, MergeOptions, SubtitleFormat, TimingOptions, download_subtitle_with_options,
+    extract_id, fetch_video_details, scan_subtitles,
 };
 
 pub fn setup_handlers(ui: &AppWindow) {
+    let labels: Vec<SharedString> = SubtitleFormat::ALL
+        .iter()
+        .map(|f| SharedString::from(f.label()))
+        .collect();
+    ui.set_subtitle_formats(Rc::new(VecModel::from(labels)).into());
+
     setup_check_link_handler(ui);
     setup_save_location_handler(ui);
+    setup_format_changed_handler(ui);
     setup_download_subtitle_handler(ui);
 }
 
+/// The format picked in the dropdown.
+fn selected_format(index: i32) -> SubtitleFormat {
+    usize::try_from(index)
+        .ok()
+        .and_then(|i| SubtitleFormat::ALL.get(i).copied())
+        .unwrap_or_default()
+}
+
 /// Decodes image bytes into raw RGBA data.
 pub fn decode_image_data(
     bytes: &[u8],
@@ -150,17 +167,29 @@ fn setup_save_location_handler(ui: &AppWindow) {
     ui.on_select_save_location(move |url| {
         let video_id = extract_id(&url).to_string();
         let ui_handle = ui_handle.clone();
+        let format = ui_handle
+            .upgrade()
+            .map(|ui| selected_format(ui.get_subtitle_format_index()))
+            .unwrap_or_default();
         tokio::spawn(async move {
-            let default_name = format!("{}.srt", video_id);
-            if let Some(file) = rfd::AsyncFileDialog::new()
-                .add_filter("Subtitle", &["srt"])
-                .set_file_name(&default_name)
-                .save_file()
-                .await
-            {
+            let default_name = format!("{}.{}", video_id, format.extension());
+            // The selected format comes first so the dialog defaults to it
+            let mut dialog = rfd::AsyncFileDialog::new()
+                .add_filter(format.label(), &[format.extension()])
+                .set_file_name(&default_name);
+            for other in SubtitleFormat::ALL.iter().filter(|f| **f != format) {
+                dialog = dialog.add_filter(other.label(), &[other.extension()]);
+            }
+            if let Some(file) = dialog.save_file().await {
                 let path = file.path().to_string_lossy().to_string();
                 let _ = slint::invoke_from_event_loop(move || {
                     if let Some(ui) = ui_handle.upgrade() {
+                        // A name with a known extension picks the format too
+                        if let Some(index) = SubtitleFormat::from_path(&path)
+                            .and_then(|f| SubtitleFormat::ALL.iter().position(|a| *a == f))
+                        {
+                            ui.set_subtitle_format_index(index as i32);
+                        }
                         ui.set_save_path(path.into());
                     }
                 });
@@ -169,15 +198,37 @@ fn setup_save_location_handler(ui: &AppWindow) {
     });
 }
 
+/// Keeps the extension of the chosen save path in line with the format.
+fn setup_format_changed_handler(ui: &AppWindow) {
+    let ui_handle = ui.as_weak();
+    ui.on_subtitle_format_changed(move |index| {
+        let Some(ui) = ui_handle.upgrade() else {
+            return;
+        };
+        let path = ui.get_save_path().to_string();
+        if path.is_empty() {
+            return;
+        }
+        let renamed = Path::new(&path).with_extension(selected_format(index).extension());
+        ui.set_save_path(renamed.to_string_lossy().to_string().into());
+    });
+}
+
 fn setup_download_subtitle_handler(ui: &AppWindow) {
     let ui_handle = ui.as_weak();
-    ui.on_download_subtitle(move |url, path, lang| {
+    ui.on_download_subtitle(move |url, path, lang, format_index, merge, fix_timing| {
         let video_id = extract_id(&url).to_string();
         let path = path.to_string();
         let lang = lang.to_string();
+        let options = DownloadOptions {
+            format: Some(selected_format(format_index)),
+            merge: merge.then(MergeOptions::default),
+            timing: fix_timing.then(TimingOptions::default),
+            ..DownloadOptions::default()
+        };
         let ui_handle = ui_handle.clone();
         tokio::spawn(async move {
-            match download_subtitle(&video_id, Some(lang), Some(path)).await {
+            match download_subtitle_with_options(&video_id, Some(lang), Some(path), options).await {
                 Ok(_) => {
                     let _ = slint::invoke_from_event_loop(move || {
                         if let Some(ui) = ui_handle.upgrade() {
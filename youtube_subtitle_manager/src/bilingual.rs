@@ -0,0 +1,136 @@
+use crate::TranscriptSegment;
+
+/// Combines two tracks of the same video into one, keeping the timing of
+/// `primary`. Each cue of `secondary` joins the primary cue it overlaps the
+/// most, or the nearest one when it overlaps none, so no text is lost; its
+/// text goes on a line of its own under the primary line. Secondary cues
+/// sharing a primary cue are joined with spaces, in order.
+pub fn combine_bilingual(
+    primary: &[TranscriptSegment],
+    secondary: &[TranscriptSegment],
+) -> Vec<TranscriptSegment> {
+    let mut combined: Vec<TranscriptSegment> = primary.to_vec();
+    combined.sort_by(|a, b| a.start.total_cmp(&b.start));
+    if combined.is_empty() {
+        return combined;
+    }
+    // No cue before this far back can reach a later start
+    let longest = combined.iter().map(|c| c.duration).fold(0.0, f64::max);
+
+    let mut lines: Vec<Vec<String>> = vec![Vec::new(); combined.len()];
+    for cue in secondary {
+        // One line per language, whatever the breaks in the source
+        let text = cue.text.split_whitespace().collect::<Vec<_>>().join(" ");
+        if text.is_empty() {
+            continue;
+        }
+
+        // 1. The primary cue overlapping it the most
+        let after = combined.partition_point(|p| p.start < cue.end());
+        let mut best: Option<(usize, f64)> = None;
+        for i in (0..after).rev() {
+            let candidate = &combined[i];
+            if candidate.start + longest <= cue.start {
+                break;
+            }
+            let overlap = candidate.end().min(cue.end()) - candidate.start.max(cue.start);
+            if overlap > 0.0 && best.is_none_or(|(_, most)| overlap >= most) {
+                best = Some((i, overlap));
+            }
+        }
+
+        // 2. Otherwise the closest one, before or after
+        let index = best.map(|(i, _)| i).unwrap_or_else(|| {
+            let gap_before = after.checked_sub(1).map(|i| cue.start - combined[i].end());
+            let gap_after = combined.get(after).map(|next| next.start - cue.end());
+            match (gap_before, gap_after) {
+                (Some(before), Some(next)) if next < before => after,
+                (Some(_), _) => after - 1,
+                _ => after,
+            }
+        });
+        lines[index].push(text);
+    }
+
+    for (cue, extra) in combined.iter_mut().zip(lines) {
+        if !extra.is_empty() {
+            cue.text = format!("{}\n{}", cue.text.trim_end(), extra.join(" "));
+        }
+    }
+    combined
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cue(start: f64, end: f64, text: &str) -> TranscriptSegment {
+        TranscriptSegment {
+            start,
+            duration: end - start,
+            text: text.to_string(),
+        }
+    }
+
+    fn texts(segments: &[TranscriptSegment]) -> Vec<&str> {
+        segments.iter().map(|s| s.text.as_str()).collect()
+    }
+
+    #[test]
+    fn matching_boundaries() {
+        let primary = [cue(0.0, 2.0, "สวัสดี"), cue(2.0, 4.0, "ขอบคุณ")];
+        let secondary = [cue(0.0, 2.0, "Hello"), cue(2.0, 4.0, "Thank\nyou")];
+        let combined = combine_bilingual(&primary, &secondary);
+        assert_eq!(texts(&combined), ["สวัสดี\nHello", "ขอบคุณ\nThank you"]);
+    }
+
+    #[test]
+    fn secondary_joins_the_largest_overlap() {
+        let primary = [cue(0.0, 3.0, "A"), cue(3.0, 6.0, "B"), cue(6.0, 9.0, "C")];
+        // Boundaries shifted against the primary track, one cue spanning two
+        let secondary = [
+            cue(0.5, 2.0, "a1"),
+            cue(2.0, 4.5, "a2"),
+            cue(4.5, 8.0, "bc"),
+            cue(8.0, 9.5, "c"),
+        ];
+        let combined = combine_bilingual(&primary, &secondary);
+        assert_eq!(texts(&combined), ["A\na1", "B\na2", "C\nbc c"]);
+    }
+
+    #[test]
+    fn long_primary_cue_is_still_found() {
+        // The long first cue reaches past the start of the second
+        let primary = [cue(0.0, 10.0, "long"), cue(1.0, 2.0, "short")];
+        let secondary = [cue(5.0, 8.0, "late")];
+        let combined = combine_bilingual(&primary, &secondary);
+        assert_eq!(texts(&combined), ["long\nlate", "short"]);
+    }
+
+    #[test]
+    fn unmatched_goes_to_the_nearest() {
+        let primary = [cue(0.0, 1.0, "A"), cue(5.0, 6.0, "B")];
+        let secondary = [
+            cue(1.5, 2.0, "near A"),
+            cue(4.0, 4.5, "near B"),
+            cue(3.0, 3.0, "tie"),
+            cue(20.0, 21.0, "after all"),
+            cue(-3.0, -2.0, "before all"),
+        ];
+        let combined = combine_bilingual(&primary, &secondary);
+        assert_eq!(
+            texts(&combined),
+            ["A\nnear A tie before all", "B\nnear B after all"]
+        );
+    }
+
+    #[test]
+    fn keeps_primary_timing() {
+        let primary = [cue(4.0, 5.0, "second"), cue(1.0, 2.0, "first")];
+        let secondary = [cue(0.0, 6.0, " "), cue(4.2, 4.4, "zwei")];
+        let combined = combine_bilingual(&primary, &secondary);
+        assert_eq!(texts(&combined), ["first", "second\nzwei"]);
+        assert_eq!((combined[1].start, combined[1].end()), (4.0, 5.0));
+        assert!(combine_bilingual(&[], &secondary).is_empty());
+    }
+}
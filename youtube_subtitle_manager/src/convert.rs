@@ -0,0 +1,87 @@
+use crate::format::write_export;
+use crate::parse::{parse_sbv, parse_srt, parse_vtt};
+use crate::{DownloadOptions, SubtitleError, SubtitleFormat, TranscriptExport, TranscriptSegment};
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Formats [`read_transcript`] can read.
+pub const READABLE_FORMATS: [SubtitleFormat; 4] = [
+    SubtitleFormat::Srt,
+    SubtitleFormat::WebVtt,
+    SubtitleFormat::Sbv,
+    SubtitleFormat::Json,
+];
+
+/// Reads a local SRT, WebVTT, SBV or JSON file, picking the format from its
+/// extension.
+///
+/// JSON is either what [`SubtitleFormat::Json`] writes, whose metadata is
+/// kept, or a bare array of `{start, duration, text}` segments. Subtitle
+/// files carry no metadata, so it is left empty.
+pub fn read_transcript(path: impl AsRef<Path>) -> Result<TranscriptExport, SubtitleError> {
+    let path = path.as_ref();
+    let format = input_format(path)?;
+    let input = fs::read_to_string(path)?;
+
+    let cues = match format {
+        SubtitleFormat::Srt => parse_srt(&input)?,
+        SubtitleFormat::WebVtt => parse_vtt(&input)?,
+        SubtitleFormat::Sbv => parse_sbv(&input)?,
+        _ => {
+            // A full export, or only its segments
+            let input = input.strip_prefix('\u{feff}').unwrap_or(&input);
+            if input.trim_start().starts_with('[') {
+                return Ok(TranscriptExport {
+                    segments: serde_json::from_str(input)?,
+                    ..TranscriptExport::default()
+                });
+            }
+            return Ok(serde_json::from_str(input)?);
+        }
+    };
+    Ok(TranscriptExport {
+        segments: cues.iter().map(TranscriptSegment::from).collect(),
+        ..TranscriptExport::default()
+    })
+}
+
+/// Converts a local subtitle file into another format. The output format is
+/// `options.format`, or else the one `output_path`'s extension names.
+/// Returns the number of cues written.
+pub fn convert_subtitle(
+    input_path: impl AsRef<Path>,
+    output_path: impl AsRef<Path>,
+    options: DownloadOptions,
+) -> Result<usize, SubtitleError> {
+    options.validate()?;
+    let output_path = output_path.as_ref();
+    let format = options
+        .format
+        .or_else(|| SubtitleFormat::from_path(output_path))
+        .ok_or_else(|| {
+            SubtitleError::Other(format!(
+                "Cannot tell the output format of '{}'. Use one of: {}.",
+                output_path.display(),
+                SubtitleFormat::ALL.map(|f| f.extension()).join(", ")
+            ))
+        })?;
+
+    let mut export = read_transcript(input_path)?;
+    export.segments = options.process(export.segments);
+    let mut file = BufWriter::new(File::create(output_path)?);
+    let written = write_export(&mut file, format, &export, &options)?;
+    file.flush()?;
+    Ok(written)
+}
+
+fn input_format(path: &Path) -> Result<SubtitleFormat, SubtitleError> {
+    SubtitleFormat::from_path(path)
+        .filter(|format| READABLE_FORMATS.contains(format))
+        .ok_or_else(|| {
+            SubtitleError::Other(format!(
+                "Cannot read '{}': the input must be a .srt, .vtt, .sbv or .json file.",
+                path.display()
+            ))
+        })
+}
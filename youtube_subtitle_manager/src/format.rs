@@ -0,0 +1,733 @@
+use crate::merge::merge_segments;
+use crate::timing::normalize_timings;
+use crate::{MergeOptions, SubtitleError, TimingOptions, TranscriptInfo, VideoDetails};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::Write;
+use std::path::Path;
+use std::str::FromStr;
+use yt_transcript_rs::FetchedTranscriptSnippet;
+
+/// File format a subtitle is written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SubtitleFormat {
+    #[default]
+    Srt,
+    WebVtt,
+    /// YouTube's own SubViewer format.
+    Sbv,
+    /// Advanced SubStation Alpha, for styled subtitles.
+    Ass,
+    /// TTML (also known as DFXP), for broadcast tools.
+    Ttml,
+    /// LRC lyrics, for audio players.
+    Lrc,
+    /// The transcript text only, cues joined into paragraphs.
+    Text,
+    /// Paragraphs that each start with a timestamp linking to that moment
+    /// of the video.
+    Markdown,
+    /// A [`TranscriptExport`]: the video's metadata and every cue.
+    Json,
+}
+
+impl SubtitleFormat {
+    /// Every format, in the order front-ends list them.
+    pub const ALL: [SubtitleFormat; 9] = [
+        SubtitleFormat::Srt,
+        SubtitleFormat::WebVtt,
+        SubtitleFormat::Sbv,
+        SubtitleFormat::Ass,
+        SubtitleFormat::Ttml,
+        SubtitleFormat::Lrc,
+        SubtitleFormat::Text,
+        SubtitleFormat::Markdown,
+        SubtitleFormat::Json,
+    ];
+
+    /// File extension without the dot.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            SubtitleFormat::Srt => "srt",
+            SubtitleFormat::WebVtt => "vtt",
+            SubtitleFormat::Sbv => "sbv",
+            SubtitleFormat::Ass => "ass",
+            SubtitleFormat::Ttml => "ttml",
+            SubtitleFormat::Lrc => "lrc",
+            SubtitleFormat::Text => "txt",
+            SubtitleFormat::Markdown => "md",
+            SubtitleFormat::Json => "json",
+        }
+    }
+
+    /// Name shown to users, e.g. in a format dropdown.
+    pub fn label(&self) -> &'static str {
+        match self {
+            SubtitleFormat::Srt => "SRT",
+            SubtitleFormat::WebVtt => "WebVTT",
+            SubtitleFormat::Sbv => "SBV (YouTube)",
+            SubtitleFormat::Ass => "ASS",
+            SubtitleFormat::Ttml => "TTML / DFXP",
+            SubtitleFormat::Lrc => "LRC",
+            SubtitleFormat::Text => "Plain text",
+            SubtitleFormat::Markdown => "Markdown",
+            SubtitleFormat::Json => "JSON",
+        }
+    }
+
+    /// The format a file name implies, if its extension is a known one.
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        let ext = path
+            .as_ref()
+            .extension()?
+            .to_string_lossy()
+            .to_ascii_lowercase();
+        ext.parse().ok()
+    }
+}
+
+impl fmt::Display for SubtitleFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+impl FromStr for SubtitleFormat {
+    type Err = SubtitleError;
+
+    /// Accepts the extension or name of a format, in any case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "srt" => Ok(SubtitleFormat::Srt),
+            "vtt" | "webvtt" => Ok(SubtitleFormat::WebVtt),
+            "sbv" => Ok(SubtitleFormat::Sbv),
+            "ass" => Ok(SubtitleFormat::Ass),
+            "ttml" | "dfxp" => Ok(SubtitleFormat::Ttml),
+            "lrc" => Ok(SubtitleFormat::Lrc),
+            "txt" | "text" => Ok(SubtitleFormat::Text),
+            "md" | "markdown" => Ok(SubtitleFormat::Markdown),
+            "json" => Ok(SubtitleFormat::Json),
+            other => Err(SubtitleError::Other(format!(
+                "Unknown subtitle format '{}'. Use one of: {}.",
+                other,
+                SubtitleFormat::ALL.map(|f| f.extension()).join(", ")
+            ))),
+        }
+    }
+}
+
+/// Options for [`download_subtitle_with_options`](crate::download_subtitle_with_options).
+#[derive(Debug, Clone, Default)]
+pub struct DownloadOptions {
+    /// Output format. When `None`, the extension of the output path decides,
+    /// falling back to SRT.
+    pub format: Option<SubtitleFormat>,
+    /// WebVTT cue settings written after every timing line, e.g.
+    /// `line:85% align:center`. Ignored for other formats.
+    pub cue_settings: Option<String>,
+    /// Merge short caption fragments (as auto-generated tracks have) into
+    /// sentence- or length-bounded cues before writing.
+    pub merge: Option<MergeOptions>,
+    /// Fix overlapping, too short and empty cues before writing, after any
+    /// merging.
+    pub timing: Option<TimingOptions>,
+    /// A second language to download along with the first. Its text is added
+    /// under the first language's line in the cue it overlaps the most.
+    pub secondary_language: Option<String>,
+}
+
+impl DownloadOptions {
+    /// Checks the merge limits, and that the cue settings are `name:value`
+    /// pairs that cannot break the timing line.
+    pub(crate) fn validate(&self) -> Result<(), SubtitleError> {
+        if let Some(merge) = &self.merge {
+            merge.validate()?;
+        }
+        if let Some(timing) = &self.timing {
+            timing.validate()?;
+        }
+        let Some(settings) = &self.cue_settings else {
+            return Ok(());
+        };
+        for setting in settings.split_whitespace() {
+            let valid = match setting.split_once(':') {
+                Some((name, value)) => {
+                    !name.is_empty() && !value.is_empty() && !setting.contains("-->")
+                }
+                None => false,
+            };
+            if !valid {
+                return Err(SubtitleError::Other(format!(
+                    "Invalid WebVTT cue setting '{}': expected name:value, e.g. line:85%",
+                    setting
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies the options that change the cues themselves.
+    pub(crate) fn process(&self, segments: Vec<TranscriptSegment>) -> Vec<TranscriptSegment> {
+        let segments = match &self.merge {
+            Some(merge) => merge_segments(&segments, merge),
+            None => segments,
+        };
+        match &self.timing {
+            Some(timing) => normalize_timings(&segments, timing).0,
+            None => segments,
+        }
+    }
+}
+
+// =========================================================================
+// SEGMENTS & TIMESTAMPS
+// Every writer takes the same segments and formats its times from a Clock.
+// =========================================================================
+
+/// One cue, times in seconds.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptSegment {
+    pub start: f64,
+    pub duration: f64,
+    pub text: String,
+}
+
+impl TranscriptSegment {
+    pub fn end(&self) -> f64 {
+        self.start + self.duration
+    }
+
+    /// The text's lines, without blank ones (they would end a cue early in
+    /// most formats).
+    fn lines(&self) -> impl Iterator<Item = &str> {
+        self.text
+            .lines()
+            .map(str::trim_end)
+            .filter(|line| !line.is_empty())
+    }
+}
+
+impl From<&FetchedTranscriptSnippet> for TranscriptSegment {
+    fn from(part: &FetchedTranscriptSnippet) -> Self {
+        TranscriptSegment {
+            start: part.start,
+            duration: part.duration,
+            text: part.text.clone(),
+        }
+    }
+}
+
+/// A time split into hours, minutes, seconds and a fraction of a second,
+/// rounded to `1 / units` of a second (1000 for milliseconds, 100 for
+/// centiseconds). Negative times clamp to zero.
+struct Clock {
+    hours: u64,
+    minutes: u64,
+    seconds: u64,
+    fraction: u64,
+}
+
+impl Clock {
+    fn new(seconds: f64, units: u64) -> Self {
+        let total = (seconds.max(0.0) * units as f64).round() as u64;
+        let whole = total / units;
+        Clock {
+            hours: whole / 3600,
+            minutes: whole / 60 % 60,
+            seconds: whole % 60,
+            fraction: total % units,
+        }
+    }
+
+    fn total_minutes(&self) -> u64 {
+        self.hours * 60 + self.minutes
+    }
+}
+
+/// `hh:mm:ss<separator>mmm`; SRT uses `,` and WebVTT `.` before the
+/// milliseconds.
+pub(crate) fn format_timestamp(seconds: f64, separator: char) -> String {
+    let c = Clock::new(seconds, 1000);
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        c.hours, c.minutes, c.seconds, separator, c.fraction
+    )
+}
+
+pub(crate) fn write_srt<W: Write>(
+    out: &mut W,
+    segments: &[TranscriptSegment],
+) -> Result<(), SubtitleError> {
+    let mut index = 0;
+    for segment in segments {
+        let text = segment.lines().collect::<Vec<_>>().join("\n");
+        if text.is_empty() {
+            continue;
+        }
+        index += 1;
+        writeln!(out, "{}", index)?;
+        writeln!(
+            out,
+            "{} --> {}",
+            format_timestamp(segment.start, ','),
+            format_timestamp(segment.end(), ',')
+        )?;
+        writeln!(out, "{}\n", text)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn write_vtt<W: Write>(
+    out: &mut W,
+    segments: &[TranscriptSegment],
+    cue_settings: Option<&str>,
+) -> Result<(), SubtitleError> {
+    writeln!(out, "WEBVTT\n")?;
+    let settings = cue_settings
+        .filter(|s| !s.trim().is_empty())
+        .map(|s| format!(" {}", s.split_whitespace().collect::<Vec<_>>().join(" ")))
+        .unwrap_or_default();
+
+    for segment in segments {
+        let text = vtt_text(segment);
+        if text.is_empty() {
+            continue;
+        }
+        writeln!(
+            out,
+            "{} --> {}{}",
+            format_timestamp(segment.start, '.'),
+            format_timestamp(segment.end(), '.'),
+            settings
+        )?;
+        writeln!(out, "{}\n", text)?;
+    }
+    Ok(())
+}
+
+/// Escapes cue text for WebVTT.
+fn vtt_text(segment: &TranscriptSegment) -> String {
+    segment
+        .lines()
+        .map(|line| {
+            line.replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// `h:mm:ss.mmm,h:mm:ss.mmm` timing lines, the format YouTube Studio
+/// exports.
+pub(crate) fn write_sbv<W: Write>(
+    out: &mut W,
+    segments: &[TranscriptSegment],
+) -> Result<(), SubtitleError> {
+    let time = |seconds: f64| {
+        let c = Clock::new(seconds, 1000);
+        format!(
+            "{}:{:02}:{:02}.{:03}",
+            c.hours, c.minutes, c.seconds, c.fraction
+        )
+    };
+    for segment in segments {
+        let text = segment.lines().collect::<Vec<_>>().join("\n");
+        if text.is_empty() {
+            continue;
+        }
+        writeln!(out, "{},{}", time(segment.start), time(segment.end()))?;
+        writeln!(out, "{}\n", text)?;
+    }
+    Ok(())
+}
+
+/// An ASS script with one `Default` style (white text with a black
+/// outline, bottom centre) and a `Dialogue` event per cue.
+pub(crate) fn write_ass<W: Write>(
+    out: &mut W,
+    segments: &[TranscriptSegment],
+) -> Result<(), SubtitleError> {
+    writeln!(out, "[Script Info]")?;
+    writeln!(out, "ScriptType: v4.00+")?;
+    writeln!(out, "PlayResX: 384")?;
+    writeln!(out, "PlayResY: 288")?;
+    writeln!(out, "WrapStyle: 0")?;
+    writeln!(out, "ScaledBorderAndShadow: yes\n")?;
+
+    writeln!(out, "[V4+ Styles]")?;
+    writeln!(
+        out,
+        "Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, \
+         BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, \
+         BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding"
+    )?;
+    writeln!(
+        out,
+        "Style: Default,Arial,20,&H00FFFFFF,&H000000FF,&H00000000,&H00000000,\
+         0,0,0,0,100,100,0,0,1,2,1,2,10,10,10,1\n"
+    )?;
+
+    writeln!(out, "[Events]")?;
+    writeln!(
+        out,
+        "Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text"
+    )?;
+    let time = |seconds: f64| {
+        let c = Clock::new(seconds, 100);
+        format!(
+            "{}:{:02}:{:02}.{:02}",
+            c.hours, c.minutes, c.seconds, c.fraction
+        )
+    };
+    for segment in segments {
+        // Braces would open an override block
+        let text = segment
+            .lines()
+            .map(|line| line.replace('{', "(").replace('}', ")"))
+            .collect::<Vec<_>>()
+            .join("\\N");
+        if text.is_empty() {
+            continue;
+        }
+        writeln!(
+            out,
+            "Dialogue: 0,{},{},Default,,0,0,0,,{}",
+            time(segment.start),
+            time(segment.end()),
+            text
+        )?;
+    }
+    Ok(())
+}
+
+/// A TTML document with a `<p>` per cue; `lang` goes in `xml:lang`.
+pub(crate) fn write_ttml<W: Write>(
+    out: &mut W,
+    segments: &[TranscriptSegment],
+    lang: &str,
+) -> Result<(), SubtitleError> {
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    // An empty xml:lang means "no language"; leave it out when unknown
+    let lang = match lang.trim() {
+        "" => String::new(),
+        lang => format!(r#" xml:lang="{}""#, xml_text(lang)),
+    };
+    writeln!(out, r#"<tt xmlns="http://www.w3.org/ns/ttml"{}>"#, lang)?;
+    writeln!(out, "  <body>")?;
+    writeln!(out, "    <div>")?;
+    for segment in segments {
+        let text = segment
+            .lines()
+            .map(xml_text)
+            .collect::<Vec<_>>()
+            .join("<br/>");
+        if text.is_empty() {
+            continue;
+        }
+        writeln!(
+            out,
+            r#"      <p begin="{}" end="{}">{}</p>"#,
+            format_timestamp(segment.start, '.'),
+            format_timestamp(segment.end(), '.'),
+            text
+        )?;
+    }
+    writeln!(out, "    </div>")?;
+    writeln!(out, "  </body>")?;
+    writeln!(out, "</tt>")?;
+    Ok(())
+}
+
+fn xml_text(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// `[mm:ss.xx]` lines, each cue on one line. LRC has no end times, so an
+/// empty line marks where a cue ends before the next one starts, clearing
+/// the display.
+pub(crate) fn write_lrc<W: Write>(
+    out: &mut W,
+    segments: &[TranscriptSegment],
+) -> Result<(), SubtitleError> {
+    let time = |seconds: f64| {
+        let c = Clock::new(seconds, 100);
+        format!(
+            "[{:02}:{:02}.{:02}]",
+            c.total_minutes(),
+            c.seconds,
+            c.fraction
+        )
+    };
+    let segments: Vec<&TranscriptSegment> = segments
+        .iter()
+        .filter(|segment| segment.lines().next().is_some())
+        .collect();
+
+    for (index, segment) in segments.iter().enumerate() {
+        let text = segment.lines().collect::<Vec<_>>().join(" ");
+        writeln!(out, "{}{}", time(segment.start), text)?;
+        // Less than a hundredth of a second apart would repeat the timestamp
+        let gap = segments
+            .get(index + 1)
+            .is_none_or(|next| next.start - segment.end() >= 0.01);
+        if gap {
+            writeln!(out, "{}", time(segment.end()))?;
+        }
+    }
+    Ok(())
+}
+
+// =========================================================================
+// TRANSCRIPTS
+// Text and Markdown drop the cue timing and join cues into paragraphs.
+// =========================================================================
+
+/// A silence this long (in seconds) between two cues starts a paragraph.
+const PARAGRAPH_PAUSE: f64 = 2.0;
+/// A paragraph this long ends at the next sentence end.
+const PARAGRAPH_TARGET: f64 = 30.0;
+/// A paragraph this long ends even mid-sentence (auto-generated captions
+/// often have no punctuation at all).
+const PARAGRAPH_MAX: f64 = 60.0;
+
+/// Whether caption text ends with sentence punctuation.
+pub(crate) fn ends_sentence(text: &str) -> bool {
+    text.trim_end()
+        .trim_end_matches(['"', '\'', '”', '’', ')'])
+        .ends_with(['.', '!', '?', '…', '。', '！', '？'])
+}
+
+struct Paragraph {
+    start: f64,
+    text: String,
+}
+
+fn paragraphs(segments: &[TranscriptSegment]) -> Vec<Paragraph> {
+    let mut paragraphs: Vec<Paragraph> = Vec::new();
+    let mut previous_end = 0.0;
+
+    for segment in segments {
+        let text = segment
+            .text
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        if text.is_empty() {
+            continue;
+        }
+
+        let continues = paragraphs.last().is_some_and(|p| {
+            let length = segment.start - p.start;
+            let sentence_ended = ends_sentence(&p.text);
+            segment.start - previous_end < PARAGRAPH_PAUSE
+                && length < PARAGRAPH_MAX
+                && !(length >= PARAGRAPH_TARGET && sentence_ended)
+        });
+        match paragraphs.last_mut() {
+            Some(paragraph) if continues => {
+                paragraph.text.push(' ');
+                paragraph.text.push_str(&text);
+            }
+            _ => paragraphs.push(Paragraph {
+                start: segment.start,
+                text,
+            }),
+        }
+        previous_end = segment.end();
+    }
+    paragraphs
+}
+
+pub(crate) fn write_text<W: Write>(
+    out: &mut W,
+    segments: &[TranscriptSegment],
+) -> Result<(), SubtitleError> {
+    let paragraphs = paragraphs(segments);
+    for (index, paragraph) in paragraphs.iter().enumerate() {
+        if index > 0 {
+            writeln!(out)?;
+        }
+        writeln!(out, "{}", paragraph.text)?;
+    }
+    Ok(())
+}
+
+/// Every paragraph starts with `[hh:mm:ss](https://youtu.be/ID?t=N)`, or
+/// with a bold `hh:mm:ss` when the video ID is unknown.
+pub(crate) fn write_markdown<W: Write>(
+    out: &mut W,
+    segments: &[TranscriptSegment],
+    video_id: &str,
+) -> Result<(), SubtitleError> {
+    let paragraphs = paragraphs(segments);
+    for (index, paragraph) in paragraphs.iter().enumerate() {
+        if index > 0 {
+            writeln!(out)?;
+        }
+        let c = Clock::new(paragraph.start.floor(), 1);
+        let time = format!("{:02}:{:02}:{:02}", c.hours, c.minutes, c.seconds);
+        let text = markdown_text(&paragraph.text);
+        if video_id.is_empty() {
+            writeln!(out, "**{}** {}", time, text)?;
+        } else {
+            let seconds = c.total_minutes() * 60 + c.seconds;
+            writeln!(
+                out,
+                "[{}](https://youtu.be/{}?t={}) {}",
+                time, video_id, seconds, text
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Escapes the characters that would turn caption text into Markdown markup.
+/// A `#` only makes a heading at the start of a line, so `#1` mid-sentence
+/// is left alone.
+fn markdown_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    let mut line_start = true;
+    for c in text.chars() {
+        if matches!(c, '\\' | '*' | '_' | '`' | '[' | ']' | '<' | '>') || (c == '#' && line_start) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+        line_start = c == '\n' || (line_start && c.is_whitespace());
+    }
+    escaped
+}
+
+// =========================================================================
+// JSON
+// =========================================================================
+
+/// What [`SubtitleFormat::Json`] writes. The metadata fields sit at the top
+/// level of the object:
+///
+/// ```json
+/// { "video_id": "…", "title": "…", "author": "…", "language_code": "en",
+///   "language": "English", "is_generated": false,
+///   "segments": [{ "start": 0.0, "duration": 2.5, "text": "…" }] }
+/// ```
+///
+/// Every format is written from one of these; metadata a subtitle file did
+/// not have is left empty.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TranscriptExport {
+    pub video_id: String,
+    #[serde(flatten)]
+    pub details: VideoDetails,
+    #[serde(flatten)]
+    pub transcript: TranscriptInfo,
+    pub segments: Vec<TranscriptSegment>,
+}
+
+pub(crate) fn write_json<W: Write>(
+    out: &mut W,
+    export: &TranscriptExport,
+) -> Result<(), SubtitleError> {
+    serde_json::to_writer_pretty(&mut *out, export)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+// =========================================================================
+// DISPATCH
+// =========================================================================
+
+/// Writes `export` in `format` and returns the number of cues written. JSON
+/// keeps every segment; the other formats skip cues with no text.
+pub(crate) fn write_export<W: Write>(
+    out: &mut W,
+    format: SubtitleFormat,
+    export: &TranscriptExport,
+    options: &DownloadOptions,
+) -> Result<usize, SubtitleError> {
+    let segments = &export.segments;
+    let written = match format {
+        SubtitleFormat::Json => segments.len(),
+        _ => segments
+            .iter()
+            .filter(|segment| segment.lines().next().is_some())
+            .count(),
+    };
+    match format {
+        SubtitleFormat::Srt => write_srt(out, segments),
+        SubtitleFormat::WebVtt => write_vtt(out, segments, options.cue_settings.as_deref()),
+        SubtitleFormat::Sbv => write_sbv(out, segments),
+        SubtitleFormat::Ass => write_ass(out, segments),
+        SubtitleFormat::Ttml => write_ttml(out, segments, &export.transcript.language_code),
+        SubtitleFormat::Lrc => write_lrc(out, segments),
+        SubtitleFormat::Text => write_text(out, segments),
+        SubtitleFormat::Markdown => write_markdown(out, segments, &export.video_id),
+        SubtitleFormat::Json => write_json(out, export),
+    }?;
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn export(texts: &[&str]) -> TranscriptExport {
+        TranscriptExport {
+            segments: texts
+                .iter()
+                .enumerate()
+                .map(|(i, text)| TranscriptSegment {
+                    start: i as f64,
+                    duration: 1.0,
+                    text: text.to_string(),
+                })
+                .collect(),
+            ..TranscriptExport::default()
+        }
+    }
+
+    fn write(format: SubtitleFormat, export: &TranscriptExport) -> (String, usize) {
+        let mut out = Vec::new();
+        let written = write_export(&mut out, format, export, &DownloadOptions::default()).unwrap();
+        (String::from_utf8(out).unwrap(), written)
+    }
+
+    #[test]
+    fn counts_only_written_cues() {
+        let export = export(&["one", "", " \n\t", "two"]);
+        let (srt, written) = write(SubtitleFormat::Srt, &export);
+        assert_eq!(written, 2);
+        assert_eq!(
+            srt,
+            "1\n00:00:00,000 --> 00:00:01,000\none\n\n2\n00:00:03,000 --> 00:00:04,000\ntwo\n\n"
+        );
+        for format in SubtitleFormat::ALL {
+            let expected = if format == SubtitleFormat::Json { 4 } else { 2 };
+            assert_eq!(write(format, &export).1, expected, "{:?}", format);
+        }
+    }
+
+    #[test]
+    fn ttml_lang_only_when_known() {
+        let mut export = export(&["one"]);
+        let (ttml, _) = write(SubtitleFormat::Ttml, &export);
+        assert!(ttml.contains("<tt xmlns=\"http://www.w3.org/ns/ttml\">\n"));
+        assert!(!ttml.contains("xml:lang"));
+
+        export.transcript.language_code = "th".to_string();
+        let (ttml, _) = write(SubtitleFormat::Ttml, &export);
+        assert!(ttml.contains("<tt xmlns=\"http://www.w3.org/ns/ttml\" xml:lang=\"th\">\n"));
+    }
+
+    #[test]
+    fn markdown_escapes_heading_marks_only_at_line_start() {
+        assert_eq!(markdown_text("we're #1 in C#"), "we're #1 in C#");
+        assert_eq!(markdown_text("# not a heading"), "\\# not a heading");
+        assert_eq!(markdown_text("one\n  ## two"), "one\n  \\## two");
+        assert_eq!(markdown_text("*a* [b]"), "\\*a\\* \\[b\\]");
+    }
+}
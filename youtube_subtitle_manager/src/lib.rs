@@ -1,5 +1,22 @@
+mod bilingual;
+mod convert;
+mod format;
+mod merge;
+mod parse;
+mod retime;
+mod timing;
+
+pub use bilingual::combine_bilingual;
+pub use convert::{convert_subtitle, read_transcript, READABLE_FORMATS};
+pub use format::{DownloadOptions, SubtitleFormat, TranscriptExport, TranscriptSegment};
+pub use merge::{merge_segments, MergeOptions};
+pub use parse::{parse_sbv, parse_srt, parse_subtitles, parse_vtt, read_subtitles, Cue};
+pub use retime::{parse_time, retime_segments, retime_subtitle, Retime, SyncPoint};
+pub use timing::{fix_subtitle, normalize_timings, TimingOptions, TimingReport};
+
+use serde::{Deserialize, Serialize};
 use std::fs::File;
-use std::io::Write;
+use std::io::{BufWriter, Write};
 use thiserror::Error;
 use yt_transcript_rs::YouTubeTranscriptApi;
 
@@ -9,16 +26,24 @@ pub enum SubtitleError {
     TranscriptError(#[source] Box<dyn std::error::Error + Send + Sync>),
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
+    #[error("JSON error: {0}")]
+    JsonError(#[from] serde_json::Error),
+    #[error("Parse error at line {line}: {message}")]
+    Parse { line: usize, message: String },
     #[error("Process error: {0}")]
     Other(String),
 }
 
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct TranscriptInfo {
     pub language_code: String,
     pub language: String,
     pub is_generated: bool,
 }
 
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct VideoDetails {
     pub title: String,
     pub author: String,
@@ -56,15 +81,6 @@ pub fn extract_id(input: &str) -> &str {
     }
 }
 
-fn format_timestamp(seconds: f64) -> String {
-    let total_ms = (seconds * 1000.0) as u64;
-    let s = total_ms / 1000;
-    let ms = total_ms % 1000;
-    let m = s / 60;
-    let h = m / 60;
-    format!("{:02}:{:02}:{:02},{:03}", h, m % 60, s % 60, ms)
-}
-
 pub async fn scan_subtitles(video_id: &str) -> Result<Vec<TranscriptInfo>, SubtitleError> {
     let id = extract_id(video_id);
     let api = YouTubeTranscriptApi::new(None, None, None)
@@ -87,11 +103,25 @@ pub async fn scan_subtitles(video_id: &str) -> Result<Vec<TranscriptInfo>, Subti
     Ok(infos)
 }
 
+/// Downloads a subtitle as SRT, or as the format the extension of
+/// `output_path` names.
 pub async fn download_subtitle(
     video_id: &str,
     lang: Option<String>,
     output_path: Option<String>,
 ) -> Result<String, SubtitleError> {
+    download_subtitle_with_options(video_id, lang, output_path, DownloadOptions::default()).await
+}
+
+/// Same as [`download_subtitle`], with the output format and its settings
+/// chosen by `options`. Returns the path written.
+pub async fn download_subtitle_with_options(
+    video_id: &str,
+    lang: Option<String>,
+    output_path: Option<String>,
+    options: DownloadOptions,
+) -> Result<String, SubtitleError> {
+    options.validate()?;
     let id = extract_id(video_id);
     let lang_code = lang.unwrap_or_else(|| "en".to_string());
 
@@ -103,26 +133,71 @@ pub async fn download_subtitle(
         .await
         .map_err(|e| SubtitleError::TranscriptError(Box::new(e)))?;
 
-    let filename = output_path.unwrap_or_else(|| format!("{}_{}.srt", id, lang_code));
-    let mut file = File::create(&filename)?;
-
-    let mut counter = 1;
-    for part in transcript.parts() {
-        let start = part.start;
-        let duration = part.duration;
-        let end = start + duration;
-        let text = &part.text;
-
-        writeln!(file, "{}", counter)?;
-        writeln!(
-            file,
-            "{} --> {}",
-            format_timestamp(start),
-            format_timestamp(end)
-        )?;
-        writeln!(file, "{}\n", text)?;
-        counter += 1;
-    }
+    // An explicit format wins, then the extension of the path, then SRT
+    let format = options
+        .format
+        .or_else(|| output_path.as_ref().and_then(SubtitleFormat::from_path))
+        .unwrap_or_default();
+    let languages = match &options.secondary_language {
+        Some(second) => format!("{}-{}", lang_code, second),
+        None => lang_code.clone(),
+    };
+    let filename =
+        output_path.unwrap_or_else(|| format!("{}_{}.{}", id, languages, format.extension()));
+    // Only JSON stores the title and author, which take another request
+    let details = if format == SubtitleFormat::Json {
+        let details = api
+            .fetch_video_details(id)
+            .await
+            .map_err(|e| SubtitleError::TranscriptError(Box::new(e)))?;
+        VideoDetails {
+            title: details.title,
+            author: details.author,
+        }
+    } else {
+        VideoDetails::default()
+    };
+    let export = TranscriptExport {
+        video_id: id.to_string(),
+        details,
+        transcript: TranscriptInfo {
+            language_code: transcript.language_code().to_string(),
+            language: transcript.language().to_string(),
+            is_generated: transcript.is_generated(),
+        },
+        segments: options.process(
+            transcript
+                .parts()
+                .iter()
+                .map(TranscriptSegment::from)
+                .collect(),
+        ),
+    };
+    let export = match &options.secondary_language {
+        Some(second) => {
+            // Each track is merged and fixed on its own, then lined up
+            let secondary = api
+                .fetch_transcript(id, &[second.as_str()], false)
+                .await
+                .map_err(|e| SubtitleError::TranscriptError(Box::new(e)))?;
+            let secondary = options.process(
+                secondary
+                    .parts()
+                    .iter()
+                    .map(TranscriptSegment::from)
+                    .collect(),
+            );
+            TranscriptExport {
+                segments: combine_bilingual(&export.segments, &secondary),
+                ..export
+            }
+        }
+        None => export,
+    };
+
+    let mut file = BufWriter::new(File::create(&filename)?);
+    format::write_export(&mut file, format, &export, &options)?;
+    file.flush()?;
 
     Ok(filename)
 }
@@ -0,0 +1,212 @@
+use crate::format::ends_sentence;
+use crate::{SubtitleError, TranscriptSegment};
+
+/// A silence this long (in seconds) between two fragments always ends a cue.
+const MERGE_PAUSE: f64 = 1.5;
+
+/// Limits for [`merge_segments`]. The defaults follow common subtitling
+/// guidelines: two lines of 42 characters, on screen for up to 7 seconds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MergeOptions {
+    /// Longest merged cue text, in characters.
+    pub max_chars: usize,
+    /// Longest merged cue, in seconds from its start to its end.
+    pub max_duration: f64,
+}
+
+impl Default for MergeOptions {
+    fn default() -> Self {
+        MergeOptions {
+            max_chars: 84,
+            max_duration: 7.0,
+        }
+    }
+}
+
+impl MergeOptions {
+    pub(crate) fn validate(&self) -> Result<(), SubtitleError> {
+        if self.max_chars == 0 {
+            return Err(SubtitleError::Other(
+                "Merged cues need a maximum of at least 1 character".to_string(),
+            ));
+        }
+        if self.max_duration.is_nan() || self.max_duration <= 0.0 {
+            return Err(SubtitleError::Other(
+                "Merged cues need a maximum duration above 0 seconds".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Merges the short, overlapping fragments of auto-generated captions into
+/// readable cues.
+///
+/// Fragments join into one cue, separated by spaces, until the cue ends a
+/// sentence, a pause follows, or the next fragment would push it past
+/// `max_chars` or `max_duration`. A single fragment over the limits stays a
+/// cue of its own. Fragments repeating the one before are dropped, and every
+/// cue ends no later than the next one starts.
+pub fn merge_segments(
+    segments: &[TranscriptSegment],
+    options: &MergeOptions,
+) -> Vec<TranscriptSegment> {
+    let mut merged: Vec<TranscriptSegment> = Vec::new();
+    let mut current: Option<TranscriptSegment> = None;
+    let mut previous_text = String::new();
+
+    for segment in segments {
+        let text = segment
+            .text
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        if text.is_empty() || text == previous_text {
+            continue;
+        }
+
+        // 1. Close the cue when the fragment does not fit in it
+        if let Some(cue) = &current {
+            let chars = cue.text.chars().count() + 1 + text.chars().count();
+            let fits = chars <= options.max_chars
+                && segment.end() - cue.start <= options.max_duration
+                && segment.start - cue.end() < MERGE_PAUSE;
+            if !fits {
+                merged.extend(current.take());
+            }
+        }
+
+        // 2. Add the fragment
+        match current.as_mut() {
+            Some(cue) => {
+                cue.text.push(' ');
+                cue.text.push_str(&text);
+                cue.duration = segment.end().max(cue.end()) - cue.start;
+            }
+            None => {
+                current = Some(TranscriptSegment {
+                    start: segment.start,
+                    duration: segment.duration,
+                    text: text.clone(),
+                })
+            }
+        }
+
+        // 3. A sentence end closes the cue
+        if current.as_ref().is_some_and(|cue| ends_sentence(&cue.text)) {
+            merged.extend(current.take());
+        }
+        previous_text = text;
+    }
+    merged.extend(current);
+
+    // 4. Fragments overlap; cut every cue off where the next begins
+    for i in 1..merged.len() {
+        let next_start = merged[i].start;
+        let cue = &mut merged[i - 1];
+        if cue.end() > next_start {
+            cue.duration = (next_start - cue.start).max(0.0);
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cue(start: f64, duration: f64, text: &str) -> TranscriptSegment {
+        TranscriptSegment {
+            start,
+            duration,
+            text: text.to_string(),
+        }
+    }
+
+    fn texts(segments: &[TranscriptSegment]) -> Vec<&str> {
+        segments.iter().map(|s| s.text.as_str()).collect()
+    }
+
+    #[test]
+    fn joins_fragments_until_sentence_end() {
+        let input = [
+            cue(0.0, 1.2, "so  today\nwe"),
+            cue(1.0, 1.0, "are going to"),
+            cue(2.0, 1.0, "cook rice."),
+            cue(3.0, 1.0, "first"),
+        ];
+        let merged = merge_segments(&input, &MergeOptions::default());
+        assert_eq!(
+            texts(&merged),
+            ["so today we are going to cook rice.", "first"]
+        );
+        assert_eq!((merged[0].start, merged[0].end()), (0.0, 3.0));
+    }
+
+    #[test]
+    fn max_chars_closes_the_cue() {
+        // 41 + 1 + 41 fits in 84; a third fragment does not
+        let input: Vec<_> = (0..3)
+            .map(|i| cue(i as f64, 1.0, &format!("{}{i}", "a".repeat(40))))
+            .collect();
+        let merged = merge_segments(&input, &MergeOptions::default());
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].text.chars().count(), 83);
+    }
+
+    #[test]
+    fn max_duration_closes_the_cue() {
+        let input: Vec<_> = (0..10)
+            .map(|i| cue(i as f64, 1.0, &format!("w{i}")))
+            .collect();
+        let merged = merge_segments(&input, &MergeOptions::default());
+        assert_eq!(texts(&merged), ["w0 w1 w2 w3 w4 w5 w6", "w7 w8 w9"]);
+        assert_eq!(merged[0].end(), 7.0);
+    }
+
+    #[test]
+    fn pause_closes_the_cue() {
+        let input = [
+            cue(0.0, 1.0, "one"),
+            cue(2.4, 1.0, "two"),
+            cue(4.9, 1.0, "three"),
+        ];
+        let merged = merge_segments(&input, &MergeOptions::default());
+        assert_eq!(texts(&merged), ["one two", "three"]);
+    }
+
+    #[test]
+    fn oversized_fragment_stays_whole() {
+        let long = "x".repeat(100);
+        let input = [cue(0.0, 9.0, &long), cue(9.0, 1.0, "after")];
+        let merged = merge_segments(&input, &MergeOptions::default());
+        assert_eq!(texts(&merged), [long.as_str(), "after"]);
+    }
+
+    #[test]
+    fn drops_repeats_and_clamps_overlaps() {
+        let input = [
+            cue(0.0, 3.0, "Hello."),
+            cue(0.5, 3.0, "Hello."),
+            cue(2.0, 1.0, "  "),
+            cue(2.0, 2.0, "Bye."),
+        ];
+        let merged = merge_segments(&input, &MergeOptions::default());
+        assert_eq!(texts(&merged), ["Hello.", "Bye."]);
+        assert_eq!(merged[0].end(), 2.0);
+    }
+
+    #[test]
+    fn rejects_bad_options() {
+        let zero_chars = MergeOptions {
+            max_chars: 0,
+            ..MergeOptions::default()
+        };
+        let no_duration = MergeOptions {
+            max_duration: f64::NAN,
+            ..MergeOptions::default()
+        };
+        assert!(zero_chars.validate().is_err());
+        assert!(no_duration.validate().is_err());
+    }
+}
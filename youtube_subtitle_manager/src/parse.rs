@@ -0,0 +1,351 @@
+use crate::{SubtitleError, SubtitleFormat, TranscriptSegment};
+use std::fs;
+use std::path::Path;
+
+/// One subtitle cue read back from a file, times in seconds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cue {
+    /// The cue's number in the file, or its 1-based position when the cue
+    /// has no numeric identifier.
+    pub index: usize,
+    pub start: f64,
+    pub end: f64,
+    /// The cue's lines, joined with `\n`.
+    pub text: String,
+}
+
+impl From<&Cue> for TranscriptSegment {
+    fn from(cue: &Cue) -> Self {
+        TranscriptSegment {
+            start: cue.start,
+            duration: cue.end - cue.start,
+            text: cue.text.clone(),
+        }
+    }
+}
+
+/// Parses SRT.
+///
+/// Tolerates a byte order mark, `\r\n` line ends, `.` instead of `,` before
+/// the milliseconds, and a missing blank line between cues (a number right
+/// before a timing line starts the next cue). Cues that end before they
+/// start are kept as they are.
+pub fn parse_srt(input: &str) -> Result<Vec<Cue>, SubtitleError> {
+    parse_cues(input, Syntax::Srt)
+}
+
+/// Parses WebVTT, with the same tolerance as [`parse_srt`]. The header,
+/// `NOTE`, `STYLE` and `REGION` blocks and cue settings are skipped; a
+/// missing `WEBVTT` line is accepted. Character references such as `&amp;`
+/// in cue text are decoded.
+pub fn parse_vtt(input: &str) -> Result<Vec<Cue>, SubtitleError> {
+    parse_cues(input, Syntax::Vtt)
+}
+
+/// Parses YouTube's SBV (`0:00:01.000,0:00:03.500` timing lines), with the
+/// same tolerance as [`parse_srt`].
+pub fn parse_sbv(input: &str) -> Result<Vec<Cue>, SubtitleError> {
+    parse_cues(input, Syntax::Sbv)
+}
+
+/// Parses WebVTT when the input starts with `WEBVTT`, SRT otherwise.
+pub fn parse_subtitles(input: &str) -> Result<Vec<Cue>, SubtitleError> {
+    if strip_bom(input).trim_start().starts_with("WEBVTT") {
+        parse_vtt(input)
+    } else {
+        parse_srt(input)
+    }
+}
+
+/// Reads a subtitle file: WebVTT or SBV when its extension says so, else
+/// WebVTT when it starts with `WEBVTT` and SRT otherwise.
+pub fn read_subtitles(path: impl AsRef<Path>) -> Result<Vec<Cue>, SubtitleError> {
+    let path = path.as_ref();
+    let input = fs::read_to_string(path)?;
+    match SubtitleFormat::from_path(path) {
+        Some(SubtitleFormat::WebVtt) => parse_vtt(&input),
+        Some(SubtitleFormat::Sbv) => parse_sbv(&input),
+        _ => parse_subtitles(&input),
+    }
+}
+
+fn strip_bom(input: &str) -> &str {
+    input.strip_prefix('\u{feff}').unwrap_or(input)
+}
+
+fn parse_error(line: usize, message: String) -> SubtitleError {
+    SubtitleError::Parse { line, message }
+}
+
+// =========================================================================
+// HELPER 1: CUES
+// =========================================================================
+#[derive(Clone, Copy, PartialEq)]
+enum Syntax {
+    Srt,
+    Vtt,
+    Sbv,
+}
+
+impl Syntax {
+    fn is_timing(self, line: &str) -> bool {
+        match self {
+            Syntax::Srt | Syntax::Vtt => line.contains("-->"),
+            Syntax::Sbv => line.split_once(',').is_some_and(|(start, end)| {
+                parse_timestamp(start).is_some() && parse_timestamp(end).is_some()
+            }),
+        }
+    }
+}
+
+fn parse_cues(input: &str, syntax: Syntax) -> Result<Vec<Cue>, SubtitleError> {
+    let lines: Vec<&str> = strip_bom(input).lines().map(str::trim_end).collect();
+    let is_timing = |i: usize| lines.get(i).is_some_and(|line| syntax.is_timing(line));
+    let vtt = syntax == Syntax::Vtt;
+
+    let mut cues = Vec::new();
+    let mut current: Option<Cue> = None;
+    let mut identifier: Option<usize> = None;
+    let mut i = 0;
+
+    // 1. Skip the WebVTT header block
+    if vtt && lines.first().is_some_and(|line| line.starts_with("WEBVTT")) {
+        while i < lines.len() && !lines[i].is_empty() {
+            i += 1;
+        }
+    }
+
+    while i < lines.len() {
+        let line = lines[i];
+        let number = i + 1;
+
+        // 2. A timing line always starts a cue, blank line or not
+        if is_timing(i) {
+            cues.extend(current.take());
+            let (start, end) = parse_timing(line, number, syntax)?;
+            current = Some(Cue {
+                index: identifier.take().unwrap_or(cues.len() + 1),
+                start,
+                end,
+                text: String::new(),
+            });
+            i += 1;
+            continue;
+        }
+
+        if line.trim().is_empty() {
+            cues.extend(current.take());
+            identifier = None;
+            i += 1;
+            continue;
+        }
+
+        match current.as_mut() {
+            // 3. Cue text, unless it is the identifier of a next cue whose
+            //    blank line is missing: a number, or any line in WebVTT
+            Some(cue) => {
+                let next_number = line.trim().parse::<usize>().ok();
+                if is_timing(i + 1) && (vtt || next_number.is_some()) {
+                    cues.extend(current.take());
+                    identifier = next_number;
+                } else {
+                    if !cue.text.is_empty() {
+                        cue.text.push('\n');
+                    }
+                    if vtt {
+                        cue.text.push_str(&decode_entities(line));
+                    } else {
+                        cue.text.push_str(line);
+                    }
+                }
+            }
+            // 4. Between cues: an identifier, a WebVTT block to skip, or junk
+            None => {
+                let block = ["NOTE", "STYLE", "REGION"]
+                    .iter()
+                    .any(|keyword| line == *keyword || line.starts_with(&format!("{} ", keyword)));
+                if vtt && block {
+                    while i < lines.len() && !lines[i].trim().is_empty() {
+                        i += 1;
+                    }
+                    continue;
+                }
+                if !is_timing(i + 1) {
+                    // A cue number: the timing line after it is what is wrong
+                    let next = lines.get(i + 1).filter(|next| !next.trim().is_empty());
+                    if let (Ok(_), Some(next)) = (line.trim().parse::<usize>(), next) {
+                        return Err(parse_error(
+                            number + 1,
+                            format!("expected a cue timing line, found '{}'", next),
+                        ));
+                    }
+                    return Err(parse_error(
+                        number,
+                        format!("expected a cue number or timing line, found '{}'", line),
+                    ));
+                }
+                identifier = line.trim().parse().ok();
+            }
+        }
+        i += 1;
+    }
+    cues.extend(current);
+    Ok(cues)
+}
+
+/// Decodes the character references WebVTT cue text uses for `&`, `<`, `>`
+/// and invisible characters.
+fn decode_entities(line: &str) -> String {
+    if !line.contains('&') {
+        return line.to_string();
+    }
+    line.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&nbsp;", "\u{a0}")
+        .replace("&lrm;", "\u{200e}")
+        .replace("&rlm;", "\u{200f}")
+        .replace("&amp;", "&")
+}
+
+// =========================================================================
+// HELPER 2: TIMESTAMPS
+// =========================================================================
+
+/// `start --> end` (`start,end` in SBV), ignoring anything after the end
+/// time (WebVTT cue settings, SRT coordinates).
+fn parse_timing(line: &str, number: usize, syntax: Syntax) -> Result<(f64, f64), SubtitleError> {
+    let separator = if syntax == Syntax::Sbv { "," } else { "-->" };
+    let (start, rest) = line.split_once(separator).unwrap_or((line, ""));
+    let end = rest.split_whitespace().next().unwrap_or("");
+
+    let parse = |text: &str| {
+        parse_timestamp(text).ok_or_else(|| {
+            parse_error(
+                number,
+                format!("invalid timestamp '{}' in '{}'", text.trim(), line),
+            )
+        })
+    };
+    Ok((parse(start)?, parse(end)?))
+}
+
+/// `hh:mm:ss,mmm`, `hh:mm:ss.mmm` or `mm:ss.mmm` in seconds. The fraction
+/// may have one to three digits or be left out.
+pub(crate) fn parse_timestamp(text: &str) -> Option<f64> {
+    let text = text.trim();
+    let (clock, fraction) = match text.rfind([',', '.']) {
+        Some(at) => (&text[..at], &text[at + 1..]),
+        None => (text, ""),
+    };
+
+    let number = |digits: &str| -> Option<u64> {
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        digits.parse().ok()
+    };
+
+    let fields = clock.split(':').map(number).collect::<Option<Vec<u64>>>()?;
+    let (hours, minutes, seconds) = match fields[..] {
+        [h, m, s] if m < 60 && s < 60 => (h, m, s),
+        [m, s] if s < 60 => (0, m, s),
+        _ => return None,
+    };
+
+    let millis = match fraction.len() {
+        0 if !text.ends_with([',', '.']) => 0,
+        1..=3 => number(fraction)? * 10u64.pow(3 - fraction.len() as u32),
+        _ => return None,
+    };
+    let total_ms = ((hours * 60 + minutes) * 60 + seconds) * 1000 + millis;
+    Some(total_ms as f64 / 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(cues: &[Cue]) -> Vec<&str> {
+        cues.iter().map(|cue| cue.text.as_str()).collect()
+    }
+
+    fn error_line(result: Result<Vec<Cue>, SubtitleError>) -> usize {
+        match result {
+            Err(SubtitleError::Parse { line, .. }) => line,
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn srt_with_bom_and_crlf() {
+        let input = "\u{feff}1\r\n00:00:01,000 --> 00:00:02,500\r\nHello\r\nworld\r\n\r\n2\r\n00:00:03,000 --> 00:00:04,000\r\nBye\r\n";
+        let cues = parse_srt(input).unwrap();
+        assert_eq!(texts(&cues), ["Hello\nworld", "Bye"]);
+        assert_eq!((cues[0].start, cues[0].end), (1.0, 2.5));
+        assert_eq!(cues[1].index, 2);
+    }
+
+    #[test]
+    fn comma_and_dot_separators() {
+        let srt = "1\n00:00:01.250 --> 00:00:02,5\nA\n";
+        let cues = parse_srt(srt).unwrap();
+        assert_eq!((cues[0].start, cues[0].end), (1.25, 2.5));
+
+        let vtt = "WEBVTT\n\n01:02.5 --> 01:03,000\nB\n";
+        let cues = parse_vtt(vtt).unwrap();
+        assert_eq!((cues[0].start, cues[0].end), (62.5, 63.0));
+    }
+
+    #[test]
+    fn srt_missing_blank_lines() {
+        let input = "1\n00:00:01,000 --> 00:00:02,000\nOne\n2\n00:00:02,000 --> 00:00:03,000\nTwo\n00:00:03,000 --> 00:00:04,000\nThree\n";
+        let cues = parse_srt(input).unwrap();
+        assert_eq!(texts(&cues), ["One", "Two", "Three"]);
+        assert_eq!(cues.iter().map(|c| c.index).collect::<Vec<_>>(), [1, 2, 3]);
+    }
+
+    #[test]
+    fn vtt_identifier_without_blank_line() {
+        let input = "WEBVTT\n\nid1\n00:00.000 --> 00:01.000\n<hi>\nid2\n00:01.000 --> 00:02.000\nthere &amp; back\n";
+        let cues = parse_vtt(input).unwrap();
+        assert_eq!(texts(&cues), ["<hi>", "there & back"]);
+    }
+
+    #[test]
+    fn vtt_skips_header_and_note_blocks() {
+        let input = "WEBVTT - title\nKind: captions\n\nNOTE a comment\nspanning lines\n\nSTYLE\n::cue { color: red }\n\n00:00.000 --> 00:01.000 line:85%\nText\n";
+        let cues = parse_vtt(input).unwrap();
+        assert_eq!(texts(&cues), ["Text"]);
+    }
+
+    #[test]
+    fn sbv_cues() {
+        let input = "0:00:01.000,0:00:02.000\nFirst\n\n0:00:02.500,0:00:03.000\nSecond\n";
+        let cues = parse_sbv(input).unwrap();
+        assert_eq!(texts(&cues), ["First", "Second"]);
+        assert_eq!(cues[1].start, 2.5);
+    }
+
+    #[test]
+    fn error_line_numbers() {
+        // Junk between cues is reported where it is
+        let junk = "1\n00:00:01,000 --> 00:00:02,000\nOne\n\nnot a cue\n";
+        assert_eq!(error_line(parse_srt(junk)), 5);
+
+        // A bad timing line after a cue number is reported, not the number
+        let bad_timing = "1\n00:00:01,000 --> 00:00:02,000\nOne\n\n2\n00:00:03 -> 00:00:04\nTwo\n";
+        assert_eq!(error_line(parse_srt(bad_timing)), 6);
+
+        // CRLF and a BOM do not shift line numbers
+        let crlf = "\u{feff}1\r\n00:00:01,000 --> 00:00:02,000\r\nOne\r\n\r\njunk\r\n";
+        assert_eq!(error_line(parse_srt(crlf)), 5);
+    }
+
+    #[test]
+    fn sniffs_webvtt() {
+        let cues = parse_subtitles("WEBVTT\n\n00:00.000 --> 00:01.000\nHi\n").unwrap();
+        assert_eq!(texts(&cues), ["Hi"]);
+        let cues = parse_subtitles("1\n00:00:00,000 --> 00:00:01,000\nHi\n").unwrap();
+        assert_eq!(texts(&cues), ["Hi"]);
+    }
+}
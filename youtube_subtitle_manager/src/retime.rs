@@ -0,0 +1,271 @@
+use crate::convert::read_transcript;
+use crate::format::write_export;
+use crate::parse::parse_timestamp;
+use crate::{DownloadOptions, SubtitleError, SubtitleFormat, TranscriptSegment};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// A point for [`Retime::Sync`]: the `cue`-th cue (counting from 1) should
+/// start at `time` seconds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SyncPoint {
+    pub cue: usize,
+    pub time: f64,
+}
+
+/// How [`retime_segments`] moves cue times.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Retime {
+    /// Adds this many seconds to every time; negative moves cues earlier.
+    Shift(f64),
+    /// Converts times for a video whose frame rate changed from `from_fps` to
+    /// `to_fps` without dropping frames, e.g. 23.976 to 25.
+    Scale { from_fps: f64, to_fps: f64 },
+    /// Stretches and shifts times so both cues start where given, for a video
+    /// that was both trimmed and sped up or slowed down.
+    Sync(SyncPoint, SyncPoint),
+}
+
+impl Retime {
+    pub(crate) fn validate(&self) -> Result<(), SubtitleError> {
+        let invalid = |message: &str| Err(SubtitleError::Other(message.to_string()));
+        let rate = |fps: f64| fps.is_finite() && fps > 0.0;
+        match *self {
+            Retime::Shift(offset) if !offset.is_finite() => invalid("The shift must be a number"),
+            Retime::Scale { from_fps, to_fps } if !rate(from_fps) || !rate(to_fps) => {
+                invalid("Frame rates must be above 0")
+            }
+            Retime::Sync(first, second) if first.cue == 0 || second.cue == 0 => {
+                invalid("Sync points count cues from 1")
+            }
+            Retime::Sync(first, second) if first.cue == second.cue => {
+                invalid("The two sync points need different cues")
+            }
+            Retime::Sync(first, second) if !first.time.is_finite() || !second.time.is_finite() => {
+                invalid("Sync point times must be numbers")
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// The `(scale, offset)` mapping each time `t` to `t * scale + offset`.
+    fn linear(&self, segments: &[TranscriptSegment]) -> Result<(f64, f64), SubtitleError> {
+        match *self {
+            Retime::Shift(offset) => Ok((1.0, offset)),
+            Retime::Scale { from_fps, to_fps } => Ok((from_fps / to_fps, 0.0)),
+            Retime::Sync(first, second) => {
+                let start = |point: SyncPoint| {
+                    segments.get(point.cue - 1).map(|s| s.start).ok_or_else(|| {
+                        SubtitleError::Other(format!(
+                            "Cannot sync on cue {}: the file has {} cues",
+                            point.cue,
+                            segments.len()
+                        ))
+                    })
+                };
+                let (from_first, from_second) = (start(first)?, start(second)?);
+                if from_first == from_second {
+                    return Err(SubtitleError::Other(format!(
+                        "Cannot sync on cues {} and {}: they start at the same time",
+                        first.cue, second.cue
+                    )));
+                }
+                let scale = (second.time - first.time) / (from_second - from_first);
+                if scale <= 0.0 {
+                    return Err(SubtitleError::Other(
+                        "Sync points must keep their cues in the same order".to_string(),
+                    ));
+                }
+                Ok((scale, first.time - from_first * scale))
+            }
+        }
+    }
+}
+
+/// Moves every cue as `retime` says. Cues that end up ending before zero are
+/// dropped and cues that start before zero are cut to start at zero.
+pub fn retime_segments(
+    segments: &[TranscriptSegment],
+    retime: &Retime,
+) -> Result<Vec<TranscriptSegment>, SubtitleError> {
+    retime.validate()?;
+    let (scale, offset) = retime.linear(segments)?;
+
+    Ok(segments
+        .iter()
+        .filter_map(|segment| {
+            let start = segment.start * scale + offset;
+            let end = segment.end() * scale + offset;
+            (end > 0.0).then(|| TranscriptSegment {
+                start: start.max(0.0),
+                duration: end - start.max(0.0),
+                text: segment.text.clone(),
+            })
+        })
+        .collect())
+}
+
+/// Reads a time given as seconds (`-2.5`) or as a timestamp (`01:12:30`,
+/// `00:00:02,500`), either optionally signed.
+pub fn parse_time(text: &str) -> Option<f64> {
+    let text = text.trim();
+    let (sign, unsigned) = match text.strip_prefix('-') {
+        Some(rest) => (-1.0, rest),
+        None => (1.0, text.strip_prefix('+').unwrap_or(text)),
+    };
+    let seconds = if unsigned.contains(':') {
+        parse_timestamp(unsigned)?
+    } else {
+        // Plain seconds; reject "inf", "NaN" and a second sign
+        if !unsigned.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+            return None;
+        }
+        unsigned.parse::<f64>().ok().filter(|s| s.is_finite())?
+    };
+    Some(sign * seconds)
+}
+
+/// Retimes a local subtitle file (see [`retime_segments`]), writing it to
+/// `output_path` in the input's format. Returns the number of cues written.
+pub fn retime_subtitle(
+    input_path: impl AsRef<Path>,
+    output_path: impl AsRef<Path>,
+    retime: Retime,
+) -> Result<usize, SubtitleError> {
+    let input_path = input_path.as_ref();
+    let mut export = read_transcript(input_path)?;
+    export.segments = retime_segments(&export.segments, &retime)?;
+
+    let format = SubtitleFormat::from_path(input_path).unwrap_or_default();
+    let mut file = BufWriter::new(File::create(output_path)?);
+    let written = write_export(&mut file, format, &export, &DownloadOptions::default())?;
+    file.flush()?;
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cues(times: &[(f64, f64)]) -> Vec<TranscriptSegment> {
+        times
+            .iter()
+            .enumerate()
+            .map(|(i, &(start, end))| TranscriptSegment {
+                start,
+                duration: end - start,
+                text: format!("cue {}", i + 1),
+            })
+            .collect()
+    }
+
+    fn times(segments: &[TranscriptSegment]) -> Vec<(f64, f64)> {
+        let ms = |t: f64| (t * 1000.0).round() / 1000.0;
+        segments
+            .iter()
+            .map(|s| (ms(s.start), ms(s.end())))
+            .collect()
+    }
+
+    fn error(result: Result<Vec<TranscriptSegment>, SubtitleError>) -> String {
+        match result {
+            Err(SubtitleError::Other(message)) => message,
+            other => panic!("expected an error, got {:?}", other),
+        }
+    }
+
+    fn point(cue: usize, time: f64) -> SyncPoint {
+        SyncPoint { cue, time }
+    }
+
+    #[test]
+    fn shift_drops_and_clamps_before_zero() {
+        let input = cues(&[(1.0, 2.0), (2.5, 4.0), (5.0, 6.0)]);
+        let shifted = retime_segments(&input, &Retime::Shift(-3.0)).unwrap();
+        // The first cue ends before zero, the second is cut to start at zero
+        assert_eq!(times(&shifted), [(0.0, 1.0), (2.0, 3.0)]);
+        assert_eq!(shifted[0].text, "cue 2");
+
+        let later = retime_segments(&input, &Retime::Shift(1.5)).unwrap();
+        assert_eq!(times(&later), [(2.5, 3.5), (4.0, 5.5), (6.5, 7.5)]);
+    }
+
+    #[test]
+    fn scale_between_frame_rates() {
+        let input = cues(&[(0.0, 25.0), (50.0, 100.0)]);
+        let scaled = retime_segments(
+            &input,
+            &Retime::Scale {
+                from_fps: 24.0,
+                to_fps: 25.0,
+            },
+        )
+        .unwrap();
+        assert_eq!(times(&scaled), [(0.0, 24.0), (48.0, 96.0)]);
+    }
+
+    #[test]
+    fn sync_stretches_and_shifts() {
+        let input = cues(&[(10.0, 11.0), (20.0, 21.0), (30.0, 31.0)]);
+        let synced = retime_segments(&input, &Retime::Sync(point(1, 0.0), point(3, 40.0))).unwrap();
+        assert_eq!(times(&synced), [(0.0, 2.0), (20.0, 22.0), (40.0, 42.0)]);
+
+        // Points given in either order; times before zero are clamped
+        let synced = retime_segments(&input, &Retime::Sync(point(3, 5.0), point(2, 0.0))).unwrap();
+        assert_eq!(times(&synced), [(0.0, 0.5), (5.0, 5.5)]);
+    }
+
+    #[test]
+    fn sync_rejects_bad_points() {
+        let input = cues(&[(1.0, 2.0), (1.0, 3.0), (4.0, 5.0)]);
+        let sync = |first, second| error(retime_segments(&input, &Retime::Sync(first, second)));
+        assert_eq!(
+            sync(point(0, 1.0), point(3, 2.0)),
+            "Sync points count cues from 1"
+        );
+        assert_eq!(
+            sync(point(2, 1.0), point(2, 2.0)),
+            "The two sync points need different cues"
+        );
+        assert_eq!(
+            sync(point(1, 1.0), point(9, 2.0)),
+            "Cannot sync on cue 9: the file has 3 cues"
+        );
+        assert_eq!(
+            sync(point(1, 1.0), point(2, 2.0)),
+            "Cannot sync on cues 1 and 2: they start at the same time"
+        );
+        assert_eq!(
+            sync(point(1, 5.0), point(3, 2.0)),
+            "Sync points must keep their cues in the same order"
+        );
+        assert_eq!(
+            sync(point(1, f64::NAN), point(3, 2.0)),
+            "Sync point times must be numbers"
+        );
+    }
+
+    #[test]
+    fn scale_rejects_bad_rates() {
+        let scale = Retime::Scale {
+            from_fps: 0.0,
+            to_fps: 25.0,
+        };
+        assert_eq!(
+            error(retime_segments(&[], &scale)),
+            "Frame rates must be above 0"
+        );
+    }
+
+    #[test]
+    fn parses_times() {
+        assert_eq!(parse_time("-2.5"), Some(-2.5));
+        assert_eq!(parse_time("+.5"), Some(0.5));
+        assert_eq!(parse_time("01:12:30"), Some(4350.0));
+        assert_eq!(parse_time("-00:00:02,500"), Some(-2.5));
+        for bad in ["", "inf", "NaN", "--1", "1:xx"] {
+            assert_eq!(parse_time(bad), None, "{bad}");
+        }
+    }
+}
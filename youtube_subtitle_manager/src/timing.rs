@@ -0,0 +1,283 @@
+use crate::convert::read_transcript;
+use crate::format::write_export;
+use crate::{DownloadOptions, SubtitleError, SubtitleFormat, TranscriptSegment};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Limits for [`normalize_timings`], in seconds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimingOptions {
+    /// Shortest time a cue stays on screen.
+    pub min_duration: f64,
+    /// Shortest pause between the end of a cue and the start of the next.
+    pub min_gap: f64,
+}
+
+impl Default for TimingOptions {
+    fn default() -> Self {
+        TimingOptions {
+            min_duration: 0.5,
+            min_gap: 0.04,
+        }
+    }
+}
+
+impl TimingOptions {
+    pub(crate) fn validate(&self) -> Result<(), SubtitleError> {
+        let valid = |value: f64| value.is_finite() && value >= 0.0;
+        if !valid(self.min_duration) || !valid(self.min_gap) {
+            return Err(SubtitleError::Other(
+                "Minimum cue duration and gap must be 0 seconds or more".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// What [`normalize_timings`] changed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TimingReport {
+    /// Cues left.
+    pub cues: usize,
+    /// Cues with no text, removed.
+    pub dropped: usize,
+    /// Cues starting too close to the one before to show on their own,
+    /// joined to it.
+    pub merged: usize,
+    /// Cues whose start or end moved.
+    pub adjusted: usize,
+}
+
+/// Puts cues in order and fixes their timing so players show one cue at a
+/// time, without ever starting a cue later than it did:
+///
+/// - cues with no text are dropped, and negative times become zero;
+/// - a cue lasts at least `min_duration`, unless the next cue starts sooner;
+/// - a cue ends `min_gap` before the next one starts. A cue with no time
+///   left at all is joined to the next one instead, as a line of its own.
+pub fn normalize_timings(
+    segments: &[TranscriptSegment],
+    options: &TimingOptions,
+) -> (Vec<TranscriptSegment>, TimingReport) {
+    let mut report = TimingReport::default();
+    let mut sorted: Vec<&TranscriptSegment> = Vec::with_capacity(segments.len());
+    for segment in segments {
+        if segment.text.trim().is_empty() {
+            report.dropped += 1;
+        } else {
+            sorted.push(segment);
+        }
+    }
+    sorted.sort_by(|a, b| a.start.total_cmp(&b.start));
+
+    // Every fixed cue with the timing it had, to count the ones that moved
+    let mut fixed: Vec<(TranscriptSegment, (f64, f64))> = Vec::with_capacity(sorted.len());
+    for segment in &sorted {
+        let start = segment.start.max(0.0);
+        // 1. Zero-length or reversed cues get the minimum duration
+        let end = segment.end().max(start + options.min_duration);
+
+        if let Some((previous, original)) = fixed.last_mut() {
+            let limit = start - options.min_gap;
+            // 2. No room for the previous cue: this one joins it
+            if limit <= previous.start {
+                previous.text = format!("{}\n{}", previous.text, segment.text);
+                previous.duration = previous.end().max(end) - previous.start;
+                original.1 = original.1.max(segment.end());
+                report.merged += 1;
+                continue;
+            }
+            // 3. End the previous cue before this one, with the gap, even
+            //    if that leaves it under the minimum
+            if previous.end() > limit {
+                previous.duration = limit - previous.start;
+            }
+        }
+
+        fixed.push((
+            TranscriptSegment {
+                start,
+                duration: end - start,
+                text: segment.text.clone(),
+            },
+            (segment.start, segment.end()),
+        ));
+    }
+
+    // Half a millisecond: below what any format writes
+    let moved = |a: f64, b: f64| (a - b).abs() >= 0.0005;
+    report.cues = fixed.len();
+    report.adjusted = fixed
+        .iter()
+        .filter(|(cue, (start, end))| moved(cue.start, *start) || moved(cue.end(), *end))
+        .count();
+    (fixed.into_iter().map(|(cue, _)| cue).collect(), report)
+}
+
+/// Normalizes the cue timing of a local subtitle file (see
+/// [`normalize_timings`]), writing it to `output_path` in the input's
+/// format.
+pub fn fix_subtitle(
+    input_path: impl AsRef<Path>,
+    output_path: impl AsRef<Path>,
+    options: TimingOptions,
+) -> Result<TimingReport, SubtitleError> {
+    options.validate()?;
+    let input_path = input_path.as_ref();
+    let mut export = read_transcript(input_path)?;
+    let (segments, report) = normalize_timings(&export.segments, &options);
+    export.segments = segments;
+
+    let format = SubtitleFormat::from_path(input_path).unwrap_or_default();
+    let mut file = BufWriter::new(File::create(output_path)?);
+    write_export(&mut file, format, &export, &DownloadOptions::default())?;
+    file.flush()?;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cue(start: f64, duration: f64, text: &str) -> TranscriptSegment {
+        TranscriptSegment {
+            start,
+            duration,
+            text: text.to_string(),
+        }
+    }
+
+    fn times(segments: &[TranscriptSegment]) -> Vec<(f64, f64)> {
+        // Rounded to the millisecond, like every format writes them
+        let ms = |t: f64| (t * 1000.0).round() / 1000.0;
+        segments
+            .iter()
+            .map(|s| (ms(s.start), ms(s.end())))
+            .collect()
+    }
+
+    #[test]
+    fn overlapping_cues_end_before_the_next() {
+        let input = [
+            cue(0.0, 3.0, "one"),
+            cue(2.0, 3.0, "two"),
+            cue(4.0, 2.0, "three"),
+        ];
+        let (fixed, report) = normalize_timings(&input, &TimingOptions::default());
+        assert_eq!(times(&fixed), [(0.0, 1.96), (2.0, 3.96), (4.0, 6.0)]);
+        assert_eq!(
+            report,
+            TimingReport {
+                cues: 3,
+                dropped: 0,
+                merged: 0,
+                adjusted: 2
+            }
+        );
+    }
+
+    #[test]
+    fn back_to_back_fragments_keep_their_starts() {
+        // Each fragment is shorter than the minimum and touches the next
+        let input: Vec<_> = (0..5)
+            .map(|i| cue(1.0 + i as f64 * 0.3, 0.3, "x"))
+            .collect();
+        let (fixed, report) = normalize_timings(&input, &TimingOptions::default());
+        let starts: Vec<f64> = times(&fixed).iter().map(|t| t.0).collect();
+        assert_eq!(starts, [1.0, 1.3, 1.6, 1.9, 2.2]);
+        assert_eq!(
+            times(&fixed)[..4],
+            [(1.0, 1.26), (1.3, 1.56), (1.6, 1.86), (1.9, 2.16)]
+        );
+        // The last one has room for the minimum
+        assert_eq!(times(&fixed)[4], (2.2, 2.7));
+        assert_eq!(report.merged, 0);
+    }
+
+    #[test]
+    fn short_fragments_do_not_cascade() {
+        // 40 fragments 50 ms apart used to push the last one out to 31 s
+        let input: Vec<_> = (0..40)
+            .map(|i| cue(10.0 + i as f64 * 0.05, 0.2, &format!("w{i}")))
+            .collect();
+        let options = TimingOptions::default();
+        let (fixed, report) = normalize_timings(&input, &options);
+
+        let last = fixed.last().unwrap();
+        assert!(
+            last.start <= 11.95 + 1e-9,
+            "last cue starts at {}",
+            last.start
+        );
+        for pair in fixed.windows(2) {
+            assert!(pair[0].end() <= pair[1].start - options.min_gap + 1e-9);
+        }
+        assert_eq!(
+            report,
+            TimingReport {
+                cues: 40,
+                dropped: 0,
+                merged: 0,
+                adjusted: 40
+            }
+        );
+        assert_eq!(times(&fixed)[39], (11.95, 12.45));
+    }
+
+    #[test]
+    fn cues_too_close_are_merged() {
+        let input = [cue(5.0, 1.0, "a"), cue(5.02, 1.5, "b"), cue(8.0, 1.0, "c")];
+        let (fixed, report) = normalize_timings(&input, &TimingOptions::default());
+        assert_eq!(fixed[0].text, "a\nb");
+        assert_eq!(times(&fixed), [(5.0, 6.52), (8.0, 9.0)]);
+        assert_eq!(
+            report,
+            TimingReport {
+                cues: 2,
+                dropped: 0,
+                merged: 1,
+                adjusted: 0
+            }
+        );
+    }
+
+    #[test]
+    fn sorts_drops_empty_and_clamps() {
+        let input = [
+            cue(3.0, 1.0, "later"),
+            cue(-1.0, 2.0, "before zero"),
+            cue(2.0, 0.5, "  "),
+            cue(1.5, 0.0, "instant"),
+        ];
+        let (fixed, report) = normalize_timings(&input, &TimingOptions::default());
+        let texts: Vec<&str> = fixed.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, ["before zero", "instant", "later"]);
+        assert_eq!(times(&fixed), [(0.0, 1.0), (1.5, 2.0), (3.0, 4.0)]);
+        assert_eq!(
+            report,
+            TimingReport {
+                cues: 3,
+                dropped: 1,
+                merged: 0,
+                adjusted: 2
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_bad_options() {
+        for options in [
+            TimingOptions {
+                min_duration: -1.0,
+                ..TimingOptions::default()
+            },
+            TimingOptions {
+                min_gap: f64::NAN,
+                ..TimingOptions::default()
+            },
+        ] {
+            assert!(options.validate().is_err());
+        }
+    }
+}